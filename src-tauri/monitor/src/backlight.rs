@@ -0,0 +1,149 @@
+//! Built-in panels through the Linux backlight class, i.e. `/sys/class/backlight/*`.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{drm, watch};
+use crate::{
+    ConnectorType, Error, ErrorKind, Feature, Interface, Monitor, MonitorBackend, MonitorDevice,
+    Reply, Result, BACKLIGHT_CAPABILITIES,
+};
+
+/// Enumerates the backlight devices of the most preferred type under `<sysfs>/class/backlight`.
+///
+/// A panel is often exposed by more than one driver; following the kernel documentation, firmware
+/// interfaces are preferred over platform ones, which are preferred over raw register access.
+#[derive(Debug, Clone)]
+pub struct BacklightBackend {
+    sysfs: PathBuf,
+}
+
+impl Default for BacklightBackend {
+    fn default() -> BacklightBackend {
+        BacklightBackend::new("/sys")
+    }
+}
+
+impl BacklightBackend {
+    pub fn new(sysfs: impl Into<PathBuf>) -> BacklightBackend {
+        BacklightBackend {
+            sysfs: sysfs.into(),
+        }
+    }
+}
+
+fn type_rank(path: &Path) -> u8 {
+    match fs::read_to_string(path.join("type"))
+        .as_deref()
+        .map(str::trim)
+    {
+        Ok("firmware") => 0,
+        Ok("platform") => 1,
+        Ok("raw") => 2,
+        _ => 3,
+    }
+}
+
+impl MonitorBackend for BacklightBackend {
+    fn enumerate(&self) -> Vec<Monitor> {
+        let Ok(entries) = fs::read_dir(self.sysfs.join("class/backlight")) else {
+            return Vec::new();
+        };
+        let mut devices: Vec<_> = entries
+            .flatten()
+            .map(|entry| (type_rank(&entry.path()), entry.file_name(), entry.path()))
+            .collect();
+        let Some(best) = devices.iter().map(|(rank, ..)| *rank).min() else {
+            return Vec::new();
+        };
+        devices.retain(|(rank, ..)| *rank == best);
+        devices.sort();
+        // there is no link between a backlight and its connector; assume a single panel
+        let panel = drm::connectors(&self.sysfs)
+            .into_iter()
+            .find(|connector| connector.connected && connector.kind.is_builtin());
+        let kind = panel
+            .as_ref()
+            .map_or(ConnectorType::Internal, |panel| panel.kind);
+        devices
+            .into_iter()
+            .map(|(_, name, path)| {
+                Monitor::new(
+                    name,
+                    Box::new(BacklightDevice {
+                        path,
+                        panel: panel.clone(),
+                    }),
+                )
+                .with_connector(kind)
+            })
+            .collect()
+    }
+
+    fn generation(&self) -> Option<u64> {
+        let mut names: Vec<_> = fs::read_dir(self.sysfs.join("class/backlight"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.file_name())
+            .collect();
+        names.sort();
+        Some(watch::hash(&names))
+    }
+}
+
+#[derive(Debug)]
+struct BacklightDevice {
+    path: PathBuf,
+    panel: Option<drm::Connector>,
+}
+
+impl BacklightDevice {
+    fn read(&self, attribute: &str) -> Result<u32> {
+        fs::read_to_string(self.path.join(attribute))?
+            .trim()
+            .parse()
+            .map_err(|e| Error::new(ErrorKind::Io, e))
+    }
+}
+
+impl MonitorDevice for BacklightDevice {
+    fn get_feature(&self, feature: Feature) -> Result<Reply> {
+        if feature != Feature::Luminance {
+            return Err(ErrorKind::NotSupported.into());
+        }
+        // actual_brightness reflects the hardware, brightness only the last request
+        let current = self
+            .read("actual_brightness")
+            .or_else(|_| self.read("brightness"))?;
+        Ok(Reply {
+            current,
+            maximum: self.read("max_brightness")?,
+            source: Interface::Backlight,
+        })
+    }
+
+    fn set_feature(&self, feature: Feature, value: u32) -> Result<()> {
+        if feature != Feature::Luminance {
+            return Err(ErrorKind::NotSupported.into());
+        }
+        let value = value.min(self.read("max_brightness")?);
+        Ok(fs::write(self.path.join("brightness"), value.to_string())?)
+    }
+
+    fn get_user_friendly_name(&self) -> Result<Option<OsString>> {
+        Ok(None)
+    }
+
+    fn get_capabilities_string(&self) -> Result<String> {
+        Ok(BACKLIGHT_CAPABILITIES.to_owned())
+    }
+
+    fn get_edid(&self) -> Result<Vec<u8>> {
+        match &self.panel {
+            Some(panel) => panel.get_edid(),
+            None => Err(ErrorKind::NotSupported.into()),
+        }
+    }
+}
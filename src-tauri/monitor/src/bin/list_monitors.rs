@@ -1,10 +1,20 @@
+use std::env;
 use std::io::{self, Write};
+use std::process;
 
-use monitor::{get_monitors, init_com};
+use monitor::{backend_from_args, enumerate, init_com, quirks};
 
 fn main() {
     init_com().unwrap();
-    let monitors = get_monitors();
+    let mut args: Vec<_> = env::args_os().skip(1).collect();
+    let backend = backend_from_args(&mut args).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    });
+    if let Some(e) = quirks::user_file_error() {
+        eprintln!("ignoring the quirks of the user: {e}");
+    }
+    let monitors = enumerate(&*backend);
     let mut stdout = io::stdout().lock();
     for monitor in monitors {
         let name = monitor.get_user_friendly_name().unwrap();
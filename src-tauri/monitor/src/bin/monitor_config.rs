@@ -1,39 +1,103 @@
 use std::env;
+use std::process;
 
-use monitor::{get_monitors, init_com, Feature};
+use monitor::verify::Outcome;
+use monitor::{backend_from_args, enumerate, init_com, input, quirks, Error, Feature, Reply};
+
+/// The number of reads `pacing` sends to learn the delay of a monitor.
+const CALIBRATION_READS: usize = 64;
+
+fn print_reply(feature: Feature, reply: Reply) {
+    match feature.value_name(reply.current) {
+        Some(name) => println!("{reply:?} ({name})"),
+        None => println!("{reply:?}"),
+    }
+}
+
+fn mismatch(requested: u32, actual: u32) -> ! {
+    eprintln!("requested {requested}, the monitor reports {actual}");
+    process::exit(1);
+}
+
+fn fail<T>(e: Error) -> T {
+    eprintln!("{e}");
+    process::exit(1);
+}
 
 fn main() {
     init_com().unwrap();
-    let mut args = env::args_os();
-    let id = args.nth(1).expect("expected monitor id");
+    let mut args: Vec<_> = env::args_os().skip(1).collect();
+    let backend = backend_from_args(&mut args).unwrap_or_else(fail);
+    if let Some(e) = quirks::user_file_error() {
+        eprintln!("ignoring the quirks of the user: {e}");
+    }
+    let mut args = args.into_iter();
+    let id = args.next().expect("expected monitor id");
+    let id = id.into_string().expect("invalid monitor id");
     let feature_name = args.next().expect("expected feature name");
     let mut feature_name = feature_name.into_string().expect("invalid feature name");
     feature_name.make_ascii_lowercase();
-    let feature = match feature_name.as_str() {
-        "luminance" => Feature::Luminance,
-        "contrast" => Feature::Contrast,
-        "brightness" => Feature::Brightness,
-        "volume" => Feature::Volume,
-        "powerstate" => Feature::PowerState,
-        _ => panic!("invalid feature name"),
-    };
-    let value = args.next().map(|value| {
-        let value = value.into_string().expect("invalid value");
-        value.parse().expect("invalid value")
-    });
-    let monitors = get_monitors();
-    let monitor = monitors
+    let monitor = enumerate(&*backend)
         .into_iter()
-        .find(|monitor| monitor.id == id)
+        .find(|monitor| monitor.matches(&id))
         .expect("monitor not found");
-    if let Some(value) = value {
-        monitor
-            .set_feature(feature, value)
-            .expect("failed to set feature");
-    } else {
-        println!(
-            "{:?}",
-            monitor.get_feature(feature).expect("failed to get feature")
-        );
+    if feature_name == "capabilities" {
+        println!("{:#?}", monitor.get_capabilities().unwrap_or_else(fail));
+        return;
+    }
+    if feature_name == "edid" {
+        println!("{:#?}", monitor.get_edid().unwrap_or_else(fail));
+        return;
+    }
+    if feature_name == "pacing" {
+        // a single command teaches the pacer nothing, so calibrate it with a burst of reads
+        let feature = args.next().map_or(Feature::Luminance, |name| {
+            let name = name.into_string().expect("invalid feature name");
+            name.parse().expect("invalid feature name")
+        });
+        for _ in 0..CALIBRATION_READS {
+            let _ = monitor.get_feature(feature);
+        }
+        println!("{:#?}", monitor.pacing_stats());
+        return;
+    }
+    let feature: Feature = feature_name.parse().expect("invalid feature name");
+    let mut verify = false;
+    let mut value = None;
+    for arg in args {
+        let arg = arg.into_string().expect("invalid argument");
+        match arg.as_str() {
+            "--verify" => verify = true,
+            _ if value.is_none() => value = Some(arg),
+            _ => panic!("unexpected argument {arg}"),
+        }
+    }
+    if let (Feature::InputSource, Some(name)) = (feature.normalize(), &value) {
+        let caps = monitor.get_capabilities().unwrap_or_default();
+        let input = input::parse(name, &caps).expect("invalid input");
+        match monitor.set_input(input).unwrap_or_else(fail) {
+            Some(reply) if verify && reply.current & 0xFF != u32::from(input) => {
+                mismatch(input.into(), reply.current)
+            }
+            Some(reply) => print_reply(feature, reply),
+            None if verify => {
+                eprintln!("switched to {input:#04x}, the monitor is no longer answering");
+                process::exit(1);
+            }
+            None => println!("switched to {input:#04x}, the monitor is no longer answering"),
+        }
+        return;
+    }
+    let value = value.map(|value| value.parse().expect("invalid value"));
+    match value {
+        Some(value) if verify => match monitor
+            .set_feature_verified(feature, value)
+            .unwrap_or_else(fail)
+        {
+            Outcome::Verified(reply) => print_reply(feature, reply),
+            Outcome::Mismatch { requested, actual } => mismatch(requested, actual),
+        },
+        Some(value) => monitor.set_feature(feature, value).unwrap_or_else(fail),
+        None => print_reply(feature, monitor.get_feature(feature).unwrap_or_else(fail)),
     }
 }
@@ -0,0 +1,187 @@
+//! Parsing of the MCCS capabilities string, e.g.
+//! `(prot(monitor)type(LCD)model(U2415)cmds(01 02 03 0C E3 F3)vcp(10 12 60(0F 11))mccs_ver(2.1))`.
+//!
+//! Real monitors emit many malformed strings: missing or unbalanced parentheses, codes run
+//! together without spaces, bare words in place of keys and trailing garbage. The parser never
+//! fails; it recovers whatever it can and leaves the rest out.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MccsVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl fmt::Display for MccsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `prot`, usually `monitor`.
+    pub protocol: Option<String>,
+    /// `type`, e.g. `LCD` or `CRT`.
+    pub kind: Option<String>,
+    pub model: Option<String>,
+    /// `cmds`, the DDC/CI opcodes the monitor accepts.
+    pub commands: Vec<u8>,
+    /// `vcp`, each supported VCP code with its allowed values; an empty list means the values
+    /// are not enumerated, as for continuous controls.
+    pub vcp: BTreeMap<u8, Vec<u8>>,
+    /// `vcpname`, names the monitor gives to manufacturer-specific codes.
+    pub vcp_names: BTreeMap<u8, String>,
+    pub mccs_version: Option<MccsVersion>,
+    /// Every other entry, such as `mswhql` or `asset_eep`, with its unparsed body.
+    pub extensions: BTreeMap<String, String>,
+}
+
+const KNOWN_KEYS: [&str; 7] = [
+    "prot", "type", "model", "cmds", "vcpname", "vcp", "mccs_ver",
+];
+
+/// Splits `s` into `key(body)` entries, closing any parentheses left open at the end.
+fn entries(s: &str) -> Vec<(&str, &str)> {
+    let mut entries = Vec::new();
+    let mut rest = s;
+    loop {
+        rest = rest.trim_start();
+        let Some(open) = rest.find('(') else {
+            break;
+        };
+        let key = rest[..open].trim();
+        let mut depth = 0;
+        let mut close = rest.len();
+        for (i, ch) in rest[open..].char_indices() {
+            match ch {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = open + i;
+                        break;
+                    }
+                }
+                _ => (),
+            }
+        }
+        let body = &rest[open + 1..close];
+        entries.push((key, body));
+        rest = rest.get(close + 1..).unwrap_or_default();
+    }
+    entries
+}
+
+/// Parses whitespace separated hex bytes, splitting runs such as `0210B6` into pairs.
+fn hex_bytes(s: &str) -> Vec<u8> {
+    s.split_whitespace()
+        .flat_map(|token| {
+            let token = token.trim_matches(|ch: char| !ch.is_ascii_hexdigit());
+            let pairs = if token.len() > 2 && token.len() % 2 == 0 {
+                token.as_bytes().chunks(2).collect()
+            } else {
+                vec![token.as_bytes()]
+            };
+            pairs
+                .into_iter()
+                .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        })
+        .collect()
+}
+
+/// Parses a `vcp` body; values in parentheses belong to the code right before them.
+fn vcp_codes(s: &str, vcp: &mut BTreeMap<u8, Vec<u8>>) {
+    let mut last = None;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let (codes, values, tail) = match rest.find('(') {
+            Some(open) => {
+                let close = rest[open..].find(')').map_or(rest.len(), |i| open + i);
+                let values = &rest[open + 1..close];
+                (
+                    &rest[..open],
+                    Some(values),
+                    rest.get(close + 1..).unwrap_or_default(),
+                )
+            }
+            None => (rest, None, ""),
+        };
+        for code in hex_bytes(codes) {
+            vcp.entry(code).or_default();
+            last = Some(code);
+        }
+        if let (Some(values), Some(code)) = (values, last) {
+            let entry = vcp.entry(code).or_default();
+            for value in hex_bytes(values) {
+                if !entry.contains(&value) {
+                    entry.push(value);
+                }
+            }
+        }
+        rest = tail;
+    }
+}
+
+fn vcp_names(s: &str, names: &mut BTreeMap<u8, String>) {
+    for (code, name) in entries(s) {
+        if let Ok(code) = u8::from_str_radix(code.trim(), 16) {
+            names.insert(code, name.trim().to_owned());
+        }
+    }
+}
+
+fn mccs_version(s: &str) -> Option<MccsVersion> {
+    let (major, minor) = s.trim().split_once('.')?;
+    Some(MccsVersion {
+        major: major.trim().parse().ok()?,
+        minor: minor.trim().parse().ok()?,
+    })
+}
+
+impl Capabilities {
+    pub fn parse(s: &str) -> Capabilities {
+        let s = s.trim_matches(|ch: char| ch == '\0' || ch.is_whitespace());
+        // the whole string should be wrapped in parentheses, but may lack either of them;
+        // stripping a closing one that belongs to the last entry is harmless as it gets closed
+        let s = s.strip_prefix('(').unwrap_or(s);
+        let s = s.strip_suffix(')').unwrap_or(s);
+
+        let mut caps = Capabilities::default();
+        for (key, body) in entries(s) {
+            let mut key = key.to_ascii_lowercase();
+            // a bare word such as a model name may run into the key that follows it
+            if !KNOWN_KEYS.contains(&key.as_str()) {
+                if let Some(known) = KNOWN_KEYS.iter().find(|known| key.ends_with(*known)) {
+                    key = known.to_string();
+                }
+            }
+            let text = || Some(body.trim().to_owned());
+            match key.as_str() {
+                "prot" => caps.protocol = text(),
+                "type" => caps.kind = text(),
+                "model" => caps.model = text(),
+                "cmds" => caps.commands.extend(hex_bytes(body)),
+                "vcp" => vcp_codes(body, &mut caps.vcp),
+                "vcpname" => vcp_names(body, &mut caps.vcp_names),
+                "mccs_ver" => caps.mccs_version = mccs_version(body),
+                "" => (),
+                _ => {
+                    caps.extensions.insert(key, body.trim().to_owned());
+                }
+            }
+        }
+        caps
+    }
+
+    pub fn supports(&self, code: u8) -> bool {
+        self.vcp.contains_key(&code)
+    }
+
+    /// The values declared for `code`, empty if the code is unsupported or not enumerated.
+    pub fn values(&self, code: u8) -> &[u8] {
+        self.vcp.get(&code).map_or(&[], Vec::as_slice)
+    }
+}
@@ -0,0 +1,65 @@
+//! The controls changed on the monitor itself, e.g. with the buttons of its OSD.
+//!
+//! MCCS monitors may flag such changes in New Control Value (VCP 0x02) and queue the VCP codes of
+//! the changed controls in Active Control (VCP 0x52), a FIFO read until it returns 0. The host
+//! then writes 1 to New Control Value to acknowledge them. Reading the flag is one command
+//! instead of one per feature, but many monitors implement neither code, and for those
+//! [`changes`] tells the caller to read every feature.
+
+use std::collections::BTreeSet;
+
+use crate::{ErrorKind, Feature, Monitor, Result};
+
+pub const NEW_CONTROL_VALUE: u8 = 0x02;
+pub const ACTIVE_CONTROL: u8 = 0x52;
+
+/// The values of New Control Value.
+const NO_NEW_VALUES: u32 = 0x01;
+const NEW_VALUES: u32 = 0x02;
+
+/// How many codes are taken from the FIFO at most, in case a monitor never returns 0.
+const MAX_CHANGES: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changes {
+    /// The VCP codes of the controls that changed; empty if none did.
+    Codes(BTreeSet<u8>),
+    /// The monitor does not tell which controls changed, so every feature has to be read.
+    Unknown,
+}
+
+/// Reads which controls changed since the last call and acknowledges them.
+///
+/// Monitors that do not support New Control Value, answer it with a value MCCS does not define,
+/// or flag changes but do not queue their codes, give [`Changes::Unknown`]; other failures are
+/// returned.
+pub fn changes(monitor: &Monitor) -> Result<Changes> {
+    let flag = match monitor.get_feature(Feature::from(NEW_CONTROL_VALUE)) {
+        Ok(reply) => reply.current & 0xFF,
+        Err(e) if e.kind() == ErrorKind::NotSupported => return Ok(Changes::Unknown),
+        Err(e) => return Err(e),
+    };
+    match flag {
+        NO_NEW_VALUES => return Ok(Changes::Codes(BTreeSet::new())),
+        NEW_VALUES => (),
+        // e.g. 0x00 or 0xFF from a monitor that does not implement the code
+        _ => return Ok(Changes::Unknown),
+    }
+    let mut codes = BTreeSet::new();
+    for _ in 0..MAX_CHANGES {
+        let code = match monitor.get_feature(Feature::from(ACTIVE_CONTROL)) {
+            Ok(reply) => (reply.current & 0xFF) as u8,
+            Err(e) if e.kind() == ErrorKind::NotSupported => break,
+            Err(e) => return Err(e),
+        };
+        if code == 0 {
+            break;
+        }
+        codes.insert(code);
+    }
+    monitor.set_feature(Feature::from(NEW_CONTROL_VALUE), NO_NEW_VALUES)?;
+    if codes.is_empty() {
+        return Ok(Changes::Unknown);
+    }
+    Ok(Changes::Codes(codes))
+}
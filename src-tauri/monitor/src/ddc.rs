@@ -0,0 +1,334 @@
+//! Encoding and decoding of DDC/CI messages, independent of how they reach the display.
+//!
+//! A message is framed as `source, 0x80 | length, payload.., checksum`, where the checksum is the
+//! XOR of every preceding byte including the destination address, which is sent by the I2C layer
+//! and therefore not part of the encoded bytes. Replies are checksummed against the virtual host
+//! address `0x50` instead.
+
+use std::error::Error;
+use std::fmt;
+
+/// The I2C address of the display, in the 8-bit form used by DDC/CI framing.
+pub const DISPLAY_ADDRESS: u8 = 0x6E;
+/// The source address of messages sent by the host.
+pub const HOST_ADDRESS: u8 = 0x51;
+/// The address replies are checksummed against.
+pub const REPLY_CHECKSUM_SEED: u8 = 0x50;
+
+/// The most data bytes a single capabilities reply fragment can carry.
+pub const MAX_CAPABILITIES_FRAGMENT: usize = 32;
+/// The most payload bytes a single message can carry, i.e. a full capabilities reply fragment.
+pub const MAX_PAYLOAD: usize = MAX_CAPABILITIES_FRAGMENT + 3;
+
+mod opcode {
+    pub const GET_VCP: u8 = 0x01;
+    pub const VCP_REPLY: u8 = 0x02;
+    pub const SET_VCP: u8 = 0x03;
+    pub const TIMING_REQUEST: u8 = 0x07;
+    pub const SAVE_SETTINGS: u8 = 0x0C;
+    pub const CAPABILITIES_REPLY: u8 = 0xE3;
+    pub const CAPABILITIES_REQUEST: u8 = 0xF3;
+    pub const TIMING_REPLY: u8 = 0x4E;
+}
+
+pub fn checksum(seed: u8, bytes: &[u8]) -> u8 {
+    bytes.iter().fold(seed, |acc, byte| acc ^ byte)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Request {
+    GetVcp { code: u8 },
+    SetVcp { code: u8, value: u16 },
+    Capabilities { offset: u16 },
+    Timing,
+    SaveSettings,
+}
+
+impl Request {
+    fn payload(&self) -> Vec<u8> {
+        match *self {
+            Request::GetVcp { code } => vec![opcode::GET_VCP, code],
+            Request::SetVcp { code, value } => {
+                let [hi, lo] = value.to_be_bytes();
+                vec![opcode::SET_VCP, code, hi, lo]
+            }
+            Request::Capabilities { offset } => {
+                let [hi, lo] = offset.to_be_bytes();
+                vec![opcode::CAPABILITIES_REQUEST, hi, lo]
+            }
+            Request::Timing => vec![opcode::TIMING_REQUEST],
+            Request::SaveSettings => vec![opcode::SAVE_SETTINGS],
+        }
+    }
+
+    /// Encodes the message as written to the display, without the destination address.
+    pub fn encode(&self) -> Vec<u8> {
+        let payload = self.payload();
+        let mut packet = Vec::with_capacity(payload.len() + 3);
+        packet.push(HOST_ADDRESS);
+        packet.push(0x80 | payload.len() as u8);
+        packet.extend_from_slice(&payload);
+        packet.push(checksum(DISPLAY_ADDRESS, &packet));
+        packet
+    }
+
+    /// The number of bytes to read for the reply, or `None` if the request has no reply.
+    pub fn reply_len(&self) -> Option<usize> {
+        match self {
+            Request::GetVcp { .. } => Some(11),
+            Request::Capabilities { .. } => Some(MAX_PAYLOAD + 3),
+            Request::Timing => Some(9),
+            Request::SetVcp { .. } | Request::SaveSettings => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VcpType {
+    SetParameter,
+    Momentary,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VcpReply {
+    pub code: u8,
+    /// Whether the display supports the code; an unsupported code carries no meaningful value.
+    pub supported: bool,
+    pub kind: VcpType,
+    pub maximum: u16,
+    pub current: u16,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimingReport {
+    pub status: u8,
+    /// In units of 10 Hz.
+    pub horizontal_frequency: u16,
+    /// In units of 0.01 Hz.
+    pub vertical_frequency: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    /// The display is busy or has nothing to say; the request may be retried later.
+    Null,
+    Vcp(VcpReply),
+    Capabilities {
+        offset: u16,
+        data: Vec<u8>,
+    },
+    Timing(TimingReport),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    Truncated { expected: usize, actual: usize },
+    Source(u8),
+    Length(u8),
+    Checksum { expected: u8, actual: u8 },
+    Opcode(u8),
+    Payload { opcode: u8, len: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { expected, actual } => {
+                write!(
+                    f,
+                    "truncated message: expected {expected} bytes, got {actual}"
+                )
+            }
+            DecodeError::Source(source) => write!(f, "unexpected source address {source:#04x}"),
+            DecodeError::Length(len) => write!(f, "invalid length byte {len:#04x}"),
+            DecodeError::Checksum { expected, actual } => {
+                write!(
+                    f,
+                    "checksum mismatch: expected {expected:#04x}, got {actual:#04x}"
+                )
+            }
+            DecodeError::Opcode(opcode) => write!(f, "unknown reply opcode {opcode:#04x}"),
+            DecodeError::Payload { opcode, len } => {
+                write!(f, "invalid payload length {len} for opcode {opcode:#04x}")
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Decodes a reply read from the display. Bytes past the checksum are ignored, so a fixed-size
+/// buffer may be passed as-is.
+pub fn decode(bytes: &[u8]) -> Result<Response, DecodeError> {
+    if bytes.len() < 3 {
+        return Err(DecodeError::Truncated {
+            expected: 3,
+            actual: bytes.len(),
+        });
+    }
+    if bytes[0] != DISPLAY_ADDRESS {
+        return Err(DecodeError::Source(bytes[0]));
+    }
+    // the timing report is the one reply sent without the high bit of the length byte
+    let len = if bytes[1] == 0x06 && bytes.get(2) == Some(&opcode::TIMING_REPLY) {
+        6
+    } else if bytes[1] & 0x80 != 0 {
+        (bytes[1] & 0x7F) as usize
+    } else {
+        return Err(DecodeError::Length(bytes[1]));
+    };
+    if len > MAX_PAYLOAD {
+        return Err(DecodeError::Length(bytes[1]));
+    }
+    if bytes.len() < len + 3 {
+        return Err(DecodeError::Truncated {
+            expected: len + 3,
+            actual: bytes.len(),
+        });
+    }
+    let expected = checksum(REPLY_CHECKSUM_SEED, &bytes[..len + 2]);
+    let actual = bytes[len + 2];
+    if expected != actual {
+        return Err(DecodeError::Checksum { expected, actual });
+    }
+    if len == 0 {
+        return Ok(Response::Null);
+    }
+
+    let opcode = bytes[2];
+    let payload = &bytes[3..len + 2];
+    let invalid = || DecodeError::Payload {
+        opcode,
+        len: payload.len(),
+    };
+    match opcode {
+        opcode::VCP_REPLY => {
+            let &[result, code, kind, max_hi, max_lo, cur_hi, cur_lo] = payload else {
+                return Err(invalid());
+            };
+            Ok(Response::Vcp(VcpReply {
+                code,
+                supported: result == 0,
+                kind: if kind == 0 {
+                    VcpType::SetParameter
+                } else {
+                    VcpType::Momentary
+                },
+                maximum: u16::from_be_bytes([max_hi, max_lo]),
+                current: u16::from_be_bytes([cur_hi, cur_lo]),
+            }))
+        }
+        opcode::CAPABILITIES_REPLY => {
+            let [hi, lo, data @ ..] = payload else {
+                return Err(invalid());
+            };
+            Ok(Response::Capabilities {
+                offset: u16::from_be_bytes([*hi, *lo]),
+                data: data.to_vec(),
+            })
+        }
+        opcode::TIMING_REPLY => {
+            let &[status, hf_hi, hf_lo, vf_hi, vf_lo] = payload else {
+                return Err(invalid());
+            };
+            Ok(Response::Timing(TimingReport {
+                status,
+                horizontal_frequency: u16::from_be_bytes([hf_hi, hf_lo]),
+                vertical_frequency: u16::from_be_bytes([vf_hi, vf_lo]),
+            }))
+        }
+        _ => Err(DecodeError::Opcode(opcode)),
+    }
+}
+
+/// Encodes a reply as the display would send it; the counterpart of [`decode`] for simulators.
+pub fn encode_response(response: &Response) -> Vec<u8> {
+    let (mut packet, len_flag) = match response {
+        Response::Null => (vec![], 0x80),
+        Response::Vcp(reply) => {
+            let [max_hi, max_lo] = reply.maximum.to_be_bytes();
+            let [cur_hi, cur_lo] = reply.current.to_be_bytes();
+            let payload = vec![
+                opcode::VCP_REPLY,
+                if reply.supported { 0 } else { 1 },
+                reply.code,
+                match reply.kind {
+                    VcpType::SetParameter => 0,
+                    VcpType::Momentary => 1,
+                },
+                max_hi,
+                max_lo,
+                cur_hi,
+                cur_lo,
+            ];
+            (payload, 0x80)
+        }
+        Response::Capabilities { offset, data } => {
+            let mut payload = vec![opcode::CAPABILITIES_REPLY];
+            payload.extend_from_slice(&offset.to_be_bytes());
+            payload.extend_from_slice(data);
+            (payload, 0x80)
+        }
+        Response::Timing(report) => {
+            let mut payload = vec![opcode::TIMING_REPLY, report.status];
+            payload.extend_from_slice(&report.horizontal_frequency.to_be_bytes());
+            payload.extend_from_slice(&report.vertical_frequency.to_be_bytes());
+            (payload, 0x00)
+        }
+    };
+    packet.splice(0..0, [DISPLAY_ADDRESS, len_flag | packet.len() as u8]);
+    packet.push(checksum(REPLY_CHECKSUM_SEED, &packet));
+    packet
+}
+
+/// Decodes a request sent by the host; the counterpart of [`Request::encode`] for simulators.
+pub fn decode_request(bytes: &[u8]) -> Result<Request, DecodeError> {
+    if bytes.len() < 3 {
+        return Err(DecodeError::Truncated {
+            expected: 3,
+            actual: bytes.len(),
+        });
+    }
+    if bytes[0] != HOST_ADDRESS {
+        return Err(DecodeError::Source(bytes[0]));
+    }
+    if bytes[1] & 0x80 == 0 {
+        return Err(DecodeError::Length(bytes[1]));
+    }
+    let len = (bytes[1] & 0x7F) as usize;
+    if bytes.len() < len + 3 {
+        return Err(DecodeError::Truncated {
+            expected: len + 3,
+            actual: bytes.len(),
+        });
+    }
+    let expected = checksum(DISPLAY_ADDRESS, &bytes[..len + 2]);
+    let actual = bytes[len + 2];
+    if expected != actual {
+        return Err(DecodeError::Checksum { expected, actual });
+    }
+    let payload = &bytes[2..len + 2];
+    let invalid = |opcode| DecodeError::Payload {
+        opcode,
+        len: payload.len(),
+    };
+    match *payload {
+        [opcode::GET_VCP, code] => Ok(Request::GetVcp { code }),
+        [opcode::SET_VCP, code, hi, lo] => Ok(Request::SetVcp {
+            code,
+            value: u16::from_be_bytes([hi, lo]),
+        }),
+        [opcode::CAPABILITIES_REQUEST, hi, lo] => Ok(Request::Capabilities {
+            offset: u16::from_be_bytes([hi, lo]),
+        }),
+        [opcode::TIMING_REQUEST] => Ok(Request::Timing),
+        [opcode::SAVE_SETTINGS] => Ok(Request::SaveSettings),
+        [opcode @ (opcode::GET_VCP
+        | opcode::SET_VCP
+        | opcode::CAPABILITIES_REQUEST
+        | opcode::TIMING_REQUEST
+        | opcode::SAVE_SETTINGS), ..] => Err(invalid(opcode)),
+        [opcode, ..] => Err(DecodeError::Opcode(opcode)),
+        [] => Err(DecodeError::Length(bytes[1])),
+    }
+}
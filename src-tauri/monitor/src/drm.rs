@@ -0,0 +1,114 @@
+//! The DRM connectors of Linux, i.e. `/sys/class/drm/card*-*`.
+//!
+//! Each connector directory tells whether a monitor is plugged in and holds its EDID. The I2C bus
+//! carrying DDC is linked as `ddc` by most drivers; DisplayPort connectors instead often have the
+//! I2C adapter of their AUX channel as a child named `i2c-N`.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::watch;
+use crate::{ConnectorType, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connector {
+    /// The name of the connector, e.g. `card0-HDMI-A-1`.
+    pub name: OsString,
+    pub path: PathBuf,
+    pub kind: ConnectorType,
+    /// Whether the status is `connected`.
+    pub connected: bool,
+    /// Whether the connector is driven by a CRTC, i.e. part of the desktop.
+    pub enabled: bool,
+    /// The name of the I2C bus carrying DDC, e.g. `i2c-5`.
+    pub ddc: Option<OsString>,
+}
+
+impl Connector {
+    pub fn get_edid(&self) -> Result<Vec<u8>> {
+        Ok(fs::read(self.path.join("edid"))?)
+    }
+}
+
+/// Parses the kind out of a connector name, e.g. `card0-eDP-1`; `None` if it is not one.
+pub fn connector_type(name: &str) -> Option<ConnectorType> {
+    let (card, rest) = name.split_once('-')?;
+    if !card
+        .strip_prefix("card")
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return None;
+    }
+    let (kind, _index) = rest.rsplit_once('-')?;
+    Some(match kind {
+        "VGA" => ConnectorType::Vga,
+        "DVI-I" | "DVI-D" | "DVI-A" => ConnectorType::Dvi,
+        "HDMI-A" | "HDMI-B" => ConnectorType::Hdmi,
+        "DP" => ConnectorType::DisplayPort,
+        "eDP" => ConnectorType::EmbeddedDisplayPort,
+        "LVDS" => ConnectorType::Lvds,
+        "DSI" => ConnectorType::Dsi,
+        _ => ConnectorType::Other,
+    })
+}
+
+fn ddc_bus(path: &Path) -> Option<OsString> {
+    if let Ok(ddc) = fs::read_link(path.join("ddc")) {
+        return ddc.file_name().map(ToOwned::to_owned);
+    }
+    let mut aux: Vec<_> = fs::read_dir(path)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.file_name())
+        .filter(|name| name.to_str().is_some_and(|name| name.starts_with("i2c-")))
+        .collect();
+    aux.sort();
+    aux.into_iter().next()
+}
+
+/// Lists the connectors under `<sysfs>/class/drm`, sorted by name.
+pub fn connectors(sysfs: &Path) -> Vec<Connector> {
+    let mut connectors: Vec<_> = fs::read_dir(sysfs.join("class/drm"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let kind = connector_type(name.to_str()?)?;
+            let path = entry.path();
+            let read = |attribute| fs::read_to_string(path.join(attribute));
+            Some(Connector {
+                connected: read("status").is_ok_and(|status| status.trim() == "connected"),
+                // older kernels lack the attribute
+                enabled: read("enabled").map_or(true, |enabled| enabled.trim() != "disabled"),
+                ddc: ddc_bus(&path),
+                name,
+                path,
+                kind,
+            })
+        })
+        .collect();
+    connectors.sort_by(|a, b| a.name.cmp(&b.name));
+    connectors
+}
+
+/// Hashes the name, status and EDID of every connector under `<sysfs>/class/drm`, which changes
+/// whenever a monitor is plugged, unplugged or replaced.
+pub fn generation(sysfs: &Path) -> u64 {
+    let mut connectors: Vec<_> = fs::read_dir(sysfs.join("class/drm"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            (
+                entry.file_name(),
+                fs::read_to_string(path.join("status")).unwrap_or_default(),
+                fs::read(path.join("edid")).unwrap_or_default(),
+            )
+        })
+        .collect();
+    connectors.sort();
+    watch::hash(&connectors)
+}
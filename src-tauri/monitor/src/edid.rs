@@ -0,0 +1,290 @@
+//! Parsing of the EDID base block (VESA E-EDID 1.3 and 1.4).
+//!
+//! Only the 128-byte base block is interpreted; extension blocks such as CTA-861 are counted but
+//! left out.
+
+use std::error::Error;
+use std::fmt;
+
+pub const BLOCK_LEN: usize = 128;
+
+const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DigitalInterface {
+    Dvi,
+    HdmiA,
+    HdmiB,
+    Mddi,
+    DisplayPort,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VideoInput {
+    Analog,
+    Digital {
+        /// Bits per color channel; only declared by EDID 1.4.
+        bit_depth: Option<u8>,
+        /// Only declared by EDID 1.4.
+        interface: Option<DigitalInterface>,
+    },
+}
+
+impl Default for VideoInput {
+    fn default() -> Self {
+        VideoInput::Digital {
+            bit_depth: None,
+            interface: None,
+        }
+    }
+}
+
+/// CIE 1931 xy coordinates.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Chromaticity {
+    pub red: (f32, f32),
+    pub green: (f32, f32),
+    pub blue: (f32, f32),
+    pub white: (f32, f32),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Edid {
+    /// The three-letter PnP ID, e.g. `DEL`.
+    pub manufacturer: String,
+    pub product_code: u16,
+    /// The binary serial number; 0 if unused.
+    pub serial_number: u32,
+    /// The week of manufacture, `None` if unspecified or if `year` is a model year.
+    pub week: Option<u8>,
+    pub year: u16,
+    pub model_year: bool,
+    pub version: (u8, u8),
+    pub input: VideoInput,
+    /// The width and height of the screen in centimetres, if declared.
+    pub size: Option<(u8, u8)>,
+    pub chromaticity: Chromaticity,
+    /// The display product name descriptor.
+    pub name: Option<String>,
+    /// The display product serial number descriptor.
+    pub serial: Option<String>,
+    /// Unspecified text descriptors, often a part number.
+    pub text: Vec<String>,
+    pub extensions: u8,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EdidError {
+    Truncated(usize),
+    Header,
+    Checksum(u8),
+}
+
+impl fmt::Display for EdidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdidError::Truncated(len) => {
+                write!(f, "truncated EDID: expected {BLOCK_LEN} bytes, got {len}")
+            }
+            EdidError::Header => f.write_str("invalid EDID header"),
+            EdidError::Checksum(sum) => write!(f, "EDID checksum mismatch: sum is {sum:#04x}"),
+        }
+    }
+}
+
+impl Error for EdidError {}
+
+fn manufacturer(id: u16) -> String {
+    [(id >> 10) & 0x1F, (id >> 5) & 0x1F, id & 0x1F]
+        .into_iter()
+        .map(|letter| match letter {
+            1..=26 => (b'A' + letter as u8 - 1) as char,
+            _ => '?',
+        })
+        .collect()
+}
+
+fn video_input(byte: u8, version: (u8, u8)) -> VideoInput {
+    if byte & 0x80 == 0 {
+        return VideoInput::Analog;
+    }
+    if version < (1, 4) {
+        return VideoInput::Digital {
+            bit_depth: None,
+            interface: None,
+        };
+    }
+    VideoInput::Digital {
+        bit_depth: match (byte >> 4) & 0x07 {
+            depth @ 1..=6 => Some(4 + depth * 2),
+            _ => None,
+        },
+        interface: match byte & 0x0F {
+            1 => Some(DigitalInterface::Dvi),
+            2 => Some(DigitalInterface::HdmiA),
+            3 => Some(DigitalInterface::HdmiB),
+            4 => Some(DigitalInterface::Mddi),
+            5 => Some(DigitalInterface::DisplayPort),
+            _ => None,
+        },
+    }
+}
+
+fn chromaticity(bytes: &[u8]) -> Chromaticity {
+    // the two low bits of each coordinate are packed into the first two bytes
+    let coordinate = |high: u8, low_byte: u8, shift: u8| {
+        (((high as u16) << 2) | ((low_byte >> shift) & 0x03) as u16) as f32 / 1024.0
+    };
+    let (rg, bw) = (bytes[0], bytes[1]);
+    Chromaticity {
+        red: (coordinate(bytes[2], rg, 6), coordinate(bytes[3], rg, 4)),
+        green: (coordinate(bytes[4], rg, 2), coordinate(bytes[5], rg, 0)),
+        blue: (coordinate(bytes[6], bw, 6), coordinate(bytes[7], bw, 4)),
+        white: (coordinate(bytes[8], bw, 2), coordinate(bytes[9], bw, 0)),
+    }
+}
+
+/// Decodes the text of a descriptor, which ends at a line feed and is padded with spaces.
+fn descriptor_text(bytes: &[u8]) -> String {
+    let end = bytes
+        .iter()
+        .position(|&b| b == b'\n')
+        .unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_owned()
+}
+
+impl Edid {
+    pub fn parse(bytes: &[u8]) -> Result<Edid, EdidError> {
+        let block = bytes
+            .get(..BLOCK_LEN)
+            .ok_or(EdidError::Truncated(bytes.len()))?;
+        if block[..8] != HEADER {
+            return Err(EdidError::Header);
+        }
+        let sum = block.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        if sum != 0 {
+            return Err(EdidError::Checksum(sum));
+        }
+
+        let version = (block[18], block[19]);
+        let (week, model_year) = match block[16] {
+            0xFF => (None, true),
+            0 => (None, false),
+            week => (Some(week), false),
+        };
+        let mut edid = Edid {
+            manufacturer: manufacturer(u16::from_be_bytes([block[8], block[9]])),
+            product_code: u16::from_le_bytes([block[10], block[11]]),
+            serial_number: u32::from_le_bytes([block[12], block[13], block[14], block[15]]),
+            week,
+            year: 1990 + block[17] as u16,
+            model_year,
+            version,
+            input: video_input(block[20], version),
+            size: (block[21] != 0 && block[22] != 0).then_some((block[21], block[22])),
+            chromaticity: chromaticity(&block[25..35]),
+            name: None,
+            serial: None,
+            text: Vec::new(),
+            extensions: block[126],
+        };
+        for descriptor in block[54..126].chunks_exact(18) {
+            // detailed timings start with a non-zero pixel clock
+            if descriptor[..3] != [0, 0, 0] {
+                continue;
+            }
+            let text = descriptor_text(&descriptor[5..]);
+            match descriptor[3] {
+                // long names may continue in a second descriptor
+                0xFC => edid.name = Some(edid.name.unwrap_or_default() + &text),
+                0xFF => edid.serial = Some(text),
+                0xFE => edid.text.push(text),
+                _ => (),
+            }
+        }
+        Ok(edid)
+    }
+
+    /// Encodes a base block without timings, e.g. for mock monitors; at most four descriptors
+    /// are kept and their text is truncated to 13 bytes.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut block = HEADER.to_vec();
+        let letters = self.manufacturer.bytes().chain([b'A'; 3]).take(3);
+        let id = letters.fold(0u16, |id, ch| {
+            (id << 5) | (ch.wrapping_sub(b'A' - 1) & 0x1F) as u16
+        });
+        block.extend(id.to_be_bytes());
+        block.extend(self.product_code.to_le_bytes());
+        block.extend(self.serial_number.to_le_bytes());
+        block.push(if self.model_year {
+            0xFF
+        } else {
+            self.week.unwrap_or(0)
+        });
+        block.push(self.year.saturating_sub(1990) as u8);
+        block.extend([self.version.0, self.version.1]);
+        block.push(match self.input {
+            VideoInput::Analog => 0,
+            VideoInput::Digital {
+                bit_depth,
+                interface,
+            } => {
+                let depth = bit_depth.map_or(0, |depth| (depth.saturating_sub(4) / 2) & 0x07);
+                let interface = match interface {
+                    None => 0,
+                    Some(DigitalInterface::Dvi) => 1,
+                    Some(DigitalInterface::HdmiA) => 2,
+                    Some(DigitalInterface::HdmiB) => 3,
+                    Some(DigitalInterface::Mddi) => 4,
+                    Some(DigitalInterface::DisplayPort) => 5,
+                };
+                0x80 | depth << 4 | interface
+            }
+        });
+        let (width, height) = self.size.unwrap_or_default();
+        block.extend([width, height, 0, 0]);
+        let Chromaticity {
+            red,
+            green,
+            blue,
+            white,
+        } = self.chromaticity;
+        let coordinates = [
+            red.0, red.1, green.0, green.1, blue.0, blue.1, white.0, white.1,
+        ]
+        .map(|value| (value.clamp(0.0, 1023.0 / 1024.0) * 1024.0).round() as u16);
+        let low = |pair: &[u16]| {
+            pair.iter()
+                .fold(0u8, |low, value| (low << 2) | (value & 0x03) as u8)
+        };
+        block.extend([low(&coordinates[..4]), low(&coordinates[4..])]);
+        block.extend(coordinates.map(|value| (value >> 2) as u8));
+        block.resize(54, 0);
+        let descriptors = self
+            .serial
+            .iter()
+            .map(|text| (0xFF, text))
+            .chain(self.name.iter().map(|text| (0xFC, text)))
+            .chain(self.text.iter().map(|text| (0xFE, text)))
+            .take(4);
+        for (tag, text) in descriptors {
+            let text = &text.as_bytes()[..text.len().min(13)];
+            let mut descriptor = [0x20; 18];
+            descriptor[..5].copy_from_slice(&[0, 0, 0, tag, 0]);
+            descriptor[5..5 + text.len()].copy_from_slice(text);
+            if text.len() < 13 {
+                descriptor[5 + text.len()] = b'\n';
+            }
+            block.extend(descriptor);
+        }
+        // unused descriptors are dummies
+        while block.len() < 126 {
+            block.extend([0, 0, 0, 0x10, 0]);
+            block.extend([0; 13]);
+        }
+        block.push(0);
+        let sum = block.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        block.push(sum.wrapping_neg());
+        block
+    }
+}
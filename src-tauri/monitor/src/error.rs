@@ -0,0 +1,178 @@
+//! The errors of the crate, classified so that callers can tell a feature a monitor lacks from a
+//! monitor that did not answer and decide what is worth retrying.
+
+use std::error;
+use std::fmt;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ddc::DecodeError;
+use crate::edid::EdidError;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorKind {
+    /// The monitor or the interface does not support the feature or the operation.
+    NotSupported,
+    /// The monitor did not answer, e.g. because it is asleep or switching inputs.
+    NoReply,
+    /// The monitor answered with a corrupt message.
+    Checksum,
+    /// The monitor or the OS took too long to answer.
+    Timeout,
+    /// Any other error of the OS or the interface.
+    Io,
+    /// No monitor has the given path or identity.
+    NoSuchMonitor,
+    /// The OS denied access to the monitor, e.g. to an i2c-dev node.
+    Permission,
+    /// The value is out of the range the interface can carry.
+    InvalidInput,
+}
+
+impl ErrorKind {
+    /// The name of the kind used by the JS side, e.g. `noReply`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::NotSupported => "notSupported",
+            ErrorKind::NoReply => "noReply",
+            ErrorKind::Checksum => "checksum",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Io => "io",
+            ErrorKind::NoSuchMonitor => "noSuchMonitor",
+            ErrorKind::Permission => "permission",
+            ErrorKind::InvalidInput => "invalidInput",
+        }
+    }
+
+    /// Whether sending the same command again may succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ErrorKind::NoReply | ErrorKind::Checksum | ErrorKind::Timeout
+        )
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ErrorKind::NotSupported => "not supported",
+            ErrorKind::NoReply => "the monitor did not reply",
+            ErrorKind::Checksum => "corrupt reply",
+            ErrorKind::Timeout => "timed out",
+            ErrorKind::Io => "I/O error",
+            ErrorKind::NoSuchMonitor => "no such monitor",
+            ErrorKind::Permission => "permission denied",
+            ErrorKind::InvalidInput => "invalid input",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// An [`ErrorKind`] and the error it was classified from, if any.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    source: Option<Box<dyn error::Error + Send + Sync>>,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn new(kind: ErrorKind, error: impl Into<Box<dyn error::Error + Send + Sync>>) -> Error {
+        Error {
+            kind,
+            source: Some(error.into()),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the underlying error, e.g. an [`io::Error`].
+    pub fn get_ref(&self) -> Option<&(dyn error::Error + Send + Sync + 'static)> {
+        self.source.as_deref()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            Some(source) => write!(f, "{}: {source}", self.kind),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.source {
+            Some(source) => Some(&**source),
+            None => None,
+        }
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error { kind, source: None }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn os_error_kind(code: i32) -> Option<ErrorKind> {
+    match code {
+        // an I2C transfer that no device acknowledged
+        libc::ENXIO | libc::EREMOTEIO => Some(ErrorKind::NoReply),
+        libc::ETIMEDOUT => Some(ErrorKind::Timeout),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn os_error_kind(_code: i32) -> Option<ErrorKind> {
+    None
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        let kind = value.raw_os_error().and_then(os_error_kind);
+        let kind = kind.unwrap_or(match value.kind() {
+            io::ErrorKind::Unsupported => ErrorKind::NotSupported,
+            io::ErrorKind::UnexpectedEof => ErrorKind::NoReply,
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => ErrorKind::Timeout,
+            io::ErrorKind::PermissionDenied => ErrorKind::Permission,
+            io::ErrorKind::InvalidInput => ErrorKind::InvalidInput,
+            _ => ErrorKind::Io,
+        });
+        Error::new(kind, value)
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(value: DecodeError) -> Self {
+        let kind = match value {
+            DecodeError::Checksum { .. } => ErrorKind::Checksum,
+            // an idle bus reads as all ones
+            DecodeError::Truncated { .. } | DecodeError::Source(_) => ErrorKind::NoReply,
+            _ => ErrorKind::Io,
+        };
+        Error::new(kind, value)
+    }
+}
+
+impl From<EdidError> for Error {
+    fn from(value: EdidError) -> Self {
+        let kind = match value {
+            EdidError::Checksum(_) => ErrorKind::Checksum,
+            _ => ErrorKind::Io,
+        };
+        Error::new(kind, value)
+    }
+}
@@ -0,0 +1,271 @@
+//! DDC/CI over an I2C bus, as exposed by the Linux i2c-dev interface.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{Read, Write};
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::ddc::{self, Request, Response};
+use crate::pacing::PacingPolicy;
+use crate::{Error, ErrorKind, Feature, Interface, MonitorDevice, Reply, Result};
+
+/// The 7-bit I2C slave address of the DDC/CI function of a display.
+pub const DDCCI_ADDRESS: u16 = 0x37;
+
+/// The display needs this long to prepare a reply after a request.
+const REPLY_DELAY: Duration = Duration::from_millis(40);
+/// The display ignores further requests this long after a write.
+const WRITE_DELAY: Duration = Duration::from_millis(50);
+/// Guards against displays that never send the terminating empty fragment.
+const MAX_CAPABILITIES_LEN: usize = 8192;
+
+/// A DDC/CI display on `bus`, which must already be addressed to [`DDCCI_ADDRESS`].
+pub struct DdcDevice<T> {
+    bus: Mutex<T>,
+    pacing: PacingPolicy,
+}
+
+impl<T> fmt::Debug for DdcDevice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DdcDevice").finish_non_exhaustive()
+    }
+}
+
+impl<T: Read + Write> DdcDevice<T> {
+    pub fn new(bus: T) -> DdcDevice<T> {
+        DdcDevice {
+            bus: Mutex::new(bus),
+            pacing: PacingPolicy::default(),
+        }
+    }
+
+    /// Replaces the default DDC/CI pacing, e.g. for a simulated display that needs none.
+    pub fn with_pacing(mut self, pacing: PacingPolicy) -> DdcDevice<T> {
+        self.pacing = pacing;
+        self
+    }
+
+    /// Sends `request` and, if it expects one, reads and decodes the reply.
+    pub fn transact(&self, request: Request) -> Result<Option<Response>> {
+        let mut bus = self.bus.lock().unwrap();
+        bus.write_all(&request.encode())?;
+        let Some(len) = request.reply_len() else {
+            sleep(WRITE_DELAY);
+            return Ok(None);
+        };
+        sleep(REPLY_DELAY);
+        let mut reply = vec![0u8; len];
+        bus.read_exact(&mut reply)?;
+        Ok(Some(ddc::decode(&reply)?))
+    }
+
+    pub fn get_vcp(&self, code: u8) -> Result<Reply> {
+        match self.transact(Request::GetVcp { code })? {
+            Some(Response::Vcp(reply)) if reply.code != code => {
+                Err(Error::new(ErrorKind::Io, "VCP reply for another code"))
+            }
+            Some(Response::Vcp(reply)) if !reply.supported => Err(ErrorKind::NotSupported.into()),
+            Some(Response::Vcp(reply)) => Ok(Reply {
+                current: reply.current as u32,
+                maximum: reply.maximum as u32,
+                source: Interface::DDCCI,
+            }),
+            Some(Response::Null) => Err(Error::new(
+                ErrorKind::NoReply,
+                "monitor replied a null message",
+            )),
+            _ => Err(Error::new(ErrorKind::Io, "unexpected reply")),
+        }
+    }
+
+    pub fn set_vcp(&self, code: u8, value: u16) -> Result<()> {
+        self.transact(Request::SetVcp { code, value }).map(drop)
+    }
+
+    /// Reads the capabilities string fragment by fragment, until an empty fragment.
+    pub fn get_capabilities_string(&self) -> Result<String> {
+        let mut caps = Vec::new();
+        loop {
+            let offset = caps.len() as u16;
+            match self.transact(Request::Capabilities { offset })? {
+                Some(Response::Capabilities { offset: o, data }) if o == offset => {
+                    if data.is_empty() {
+                        break;
+                    }
+                    caps.extend_from_slice(&data);
+                }
+                Some(Response::Null) => {
+                    return Err(Error::new(
+                        ErrorKind::NoReply,
+                        "monitor replied a null message",
+                    ))
+                }
+                _ => return Err(Error::new(ErrorKind::Io, "unexpected reply")),
+            }
+            if caps.len() > MAX_CAPABILITIES_LEN {
+                return Err(Error::new(ErrorKind::Io, "capabilities string too long"));
+            }
+            sleep(WRITE_DELAY);
+        }
+        let len = caps.iter().position(|&ch| ch == 0).unwrap_or(caps.len());
+        caps.truncate(len);
+        Ok(String::from_utf8_lossy(&caps).into_owned())
+    }
+}
+
+impl<T: Read + Write + Send> MonitorDevice for DdcDevice<T> {
+    fn get_feature(&self, feature: Feature) -> Result<Reply> {
+        self.get_vcp(feature.vcp_code())
+    }
+
+    fn set_feature(&self, feature: Feature, value: u32) -> Result<()> {
+        let value = u16::try_from(value).map_err(|_| Error::from(ErrorKind::InvalidInput))?;
+        self.set_vcp(feature.vcp_code(), value)
+    }
+
+    fn get_user_friendly_name(&self) -> Result<Option<OsString>> {
+        Ok(None)
+    }
+
+    fn get_capabilities_string(&self) -> Result<String> {
+        DdcDevice::get_capabilities_string(self)
+    }
+
+    /// The EDID lives at another address of the bus; the OS provides it instead.
+    fn get_edid(&self) -> Result<Vec<u8>> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    fn pacing(&self) -> PacingPolicy {
+        self.pacing.clone()
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::{open, I2cBackend};
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ffi::OsString;
+    use std::fs::{File, OpenOptions};
+    use std::io;
+    use std::os::fd::AsRawFd;
+    use std::path::{Path, PathBuf};
+
+    use super::{DdcDevice, DDCCI_ADDRESS};
+    use crate::drm;
+    use crate::pacing::PacingPolicy;
+    use crate::{Feature, Monitor, MonitorBackend, MonitorDevice, Reply, Result};
+
+    const I2C_SLAVE: u64 = 0x0703;
+
+    /// Opens an i2c-dev node, e.g. `/dev/i2c-3`, and addresses it to the DDC/CI function.
+    pub fn open(path: impl AsRef<Path>) -> Result<DdcDevice<File>> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        if unsafe {
+            libc::ioctl(
+                file.as_raw_fd(),
+                I2C_SLAVE as _,
+                DDCCI_ADDRESS as libc::c_ulong,
+            )
+        } < 0
+        {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(DdcDevice::new(file))
+    }
+
+    /// Enumerates the connected and enabled DRM connectors of external monitors that have a DDC
+    /// bus.
+    #[derive(Debug, Clone)]
+    pub struct I2cBackend {
+        sysfs: PathBuf,
+        dev: PathBuf,
+    }
+
+    impl Default for I2cBackend {
+        fn default() -> I2cBackend {
+            I2cBackend::new("/sys", "/dev")
+        }
+    }
+
+    impl I2cBackend {
+        pub fn new(sysfs: impl Into<PathBuf>, dev: impl Into<PathBuf>) -> I2cBackend {
+            I2cBackend {
+                sysfs: sysfs.into(),
+                dev: dev.into(),
+            }
+        }
+    }
+
+    impl MonitorBackend for I2cBackend {
+        fn enumerate(&self) -> Vec<Monitor> {
+            let mut monitors = Vec::new();
+            for connector in drm::connectors(&self.sysfs) {
+                // built-in panels are left to the backlight backend
+                if !connector.connected || !connector.enabled || connector.kind.is_builtin() {
+                    continue;
+                }
+                let Some(bus) = &connector.ddc else {
+                    continue;
+                };
+                // the node may be inaccessible to the current user
+                if let Ok(ddc) = open(self.dev.join(bus)) {
+                    let kind = connector.kind;
+                    monitors.push(
+                        Monitor::new(
+                            connector.name.clone(),
+                            Box::new(ConnectorDevice { connector, ddc }),
+                        )
+                        .with_connector(kind),
+                    );
+                }
+            }
+            monitors
+        }
+
+        fn generation(&self) -> Option<u64> {
+            Some(drm::generation(&self.sysfs))
+        }
+    }
+
+    /// A display on a DRM connector, which provides the EDID.
+    #[derive(Debug)]
+    struct ConnectorDevice {
+        connector: drm::Connector,
+        ddc: DdcDevice<File>,
+    }
+
+    impl MonitorDevice for ConnectorDevice {
+        fn get_feature(&self, feature: Feature) -> Result<Reply> {
+            self.ddc.get_feature(feature)
+        }
+
+        fn set_feature(&self, feature: Feature, value: u32) -> Result<()> {
+            self.ddc.set_feature(feature, value)
+        }
+
+        fn get_user_friendly_name(&self) -> Result<Option<OsString>> {
+            Ok(None)
+        }
+
+        fn get_capabilities_string(&self) -> Result<String> {
+            self.ddc.get_capabilities_string()
+        }
+
+        fn get_edid(&self) -> Result<Vec<u8>> {
+            self.connector.get_edid()
+        }
+
+        fn pacing(&self) -> PacingPolicy {
+            self.ddc.pacing()
+        }
+
+        fn bus(&self) -> Option<String> {
+            let bus = self.connector.ddc.as_ref()?;
+            Some(bus.to_string_lossy().into_owned())
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Identities of monitors that survive port, cable and dock changes.
+//!
+//! [`Monitor::id`] is the path the OS gives to the port a monitor is attached to. A [`MonitorId`]
+//! is instead derived from the EDID: the PnP manufacturer ID, the product code and the serial
+//! number, e.g. `DEL-A0BA-30424C4C`. When the binary serial number is 0, the serial number
+//! descriptor is used instead, e.g. `DEL-A0BA-CFV9N7BJ0LLB`, and if there is none either the
+//! identity is just `DEL-A0BA`.
+//!
+//! Identical monitors that report no serial number, or the same one, end up with the same
+//! identity. They are told apart by their position in the order of the OS paths: the first keeps
+//! the plain identity and the following ones get `#2`, `#3` and so on. Such monitors may swap
+//! identities when they swap ports, but nothing better can be done without a serial number.
+//!
+//! Monitors without a readable EDID are identified by their OS path.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::edid::Edid;
+use crate::Monitor;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MonitorId(String);
+
+impl MonitorId {
+    pub fn from_edid(edid: &Edid) -> MonitorId {
+        let mut id = format!("{}-{:04X}", edid.manufacturer, edid.product_code);
+        let serial = edid
+            .serial
+            .as_deref()
+            .filter(|serial| !serial.is_empty())
+            .map(|serial| serial.replace(|ch: char| !ch.is_ascii_alphanumeric(), ""));
+        match (edid.serial_number, serial) {
+            (0, Some(serial)) => id = format!("{id}-{serial}"),
+            (0, None) => (),
+            (serial, _) => id = format!("{id}-{serial:08X}"),
+        }
+        MonitorId(id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for MonitorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for MonitorId {
+    fn from(value: String) -> Self {
+        MonitorId(value)
+    }
+}
+
+/// Assigns the identities of `monitors`, reading their EDIDs.
+pub fn identify(monitors: &mut [Monitor]) {
+    let edids: Vec<_> = monitors
+        .iter()
+        .map(|monitor| monitor.get_edid().ok())
+        .collect();
+    identify_with(monitors, &edids);
+}
+
+/// Assigns the identities of `monitors` from their EDIDs, already read.
+pub(crate) fn identify_with(monitors: &mut [Monitor], edids: &[Option<Edid>]) {
+    let mut order: Vec<_> = (0..monitors.len()).collect();
+    order.sort_by(|a, b| monitors[*a].id.cmp(&monitors[*b].id));
+    let mut seen = BTreeMap::<MonitorId, usize>::new();
+    for i in order {
+        let monitor = &mut monitors[i];
+        let id = match &edids[i] {
+            Some(edid) => MonitorId::from_edid(edid),
+            None => MonitorId(monitor.id.to_string_lossy().into_owned()),
+        };
+        let count = seen.entry(id.clone()).or_default();
+        *count += 1;
+        monitor.identity = match *count {
+            1 => id,
+            n => MonitorId(format!("{id}#{n}")),
+        };
+    }
+}
@@ -0,0 +1,144 @@
+//! Input source selection (VCP 0x60).
+//!
+//! Many monitors stop answering DDC/CI for a second or two after switching inputs, and some
+//! switch before acknowledging the write. [`switch`] therefore treats a failed write as
+//! tentative and polls the monitor until it reports the new input or a deadline passes.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::capabilities::Capabilities;
+use crate::{vcp, ErrorKind, Feature, Monitor, Reply, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    pub value: u8,
+    /// The MCCS name, e.g. `HDMI-1`, or the hex value for a manufacturer-specific input.
+    pub name: String,
+}
+
+impl Input {
+    pub fn new(value: u8) -> Input {
+        Input {
+            value,
+            name: vcp::value_name(0x60, value as u32)
+                .map_or_else(|| format!("{value:#04x}"), str::to_owned),
+        }
+    }
+}
+
+/// Returns the inputs declared by `caps`, empty if the monitor does not enumerate them.
+pub fn inputs(caps: &Capabilities) -> Vec<Input> {
+    caps.values(Feature::InputSource.vcp_code())
+        .iter()
+        .map(|value| Input::new(*value))
+        .collect()
+}
+
+const ABBREVIATIONS: [(&str, &str); 3] = [
+    ("dp", "displayport"),
+    ("typec", "usbc"),
+    ("usbtypec", "usbc"),
+];
+
+fn key(s: &str) -> String {
+    let key: String = s
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|ch| ch.to_ascii_lowercase())
+        .collect();
+    for (short, long) in ABBREVIATIONS {
+        if let Some(rest) = key.strip_prefix(short) {
+            if rest.is_empty() || rest.bytes().all(|b| b.is_ascii_digit()) {
+                return format!("{long}{rest}");
+            }
+        }
+    }
+    key
+}
+
+/// Resolves an input name such as `hdmi1`, `DP-2` or `0x11`.
+///
+/// Names are matched against the inputs declared by `caps`, or against all standard inputs if it
+/// declares none. A name without a number, such as `hdmi`, matches if only one input has it.
+pub fn parse(s: &str, caps: &Capabilities) -> Option<u8> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        return u8::from_str_radix(hex, 16).ok();
+    }
+    if let Ok(value) = s.parse() {
+        return Some(value);
+    }
+    let candidates = match inputs(caps) {
+        declared if !declared.is_empty() => declared,
+        _ => vcp::INPUT_SOURCES
+            .iter()
+            .map(|(value, _)| Input::new(*value))
+            .collect(),
+    };
+    let key = key(s);
+    if let Some(input) = candidates
+        .iter()
+        .find(|input| self::key(&input.name) == key)
+    {
+        return Some(input.value);
+    }
+    let mut prefixed = candidates.iter().filter(|input| {
+        self::key(&input.name)
+            .strip_prefix(&key)
+            .is_some_and(|rest| rest.bytes().all(|b| b.is_ascii_digit()))
+    });
+    match (prefixed.next(), prefixed.next()) {
+        (Some(input), None) => Some(input.value),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchTiming {
+    /// How long to wait after the write before the first read.
+    pub settle: Duration,
+    /// How long to wait between reads that fail.
+    pub poll: Duration,
+    /// How long after the write to give up on the monitor answering.
+    pub timeout: Duration,
+}
+
+impl Default for SwitchTiming {
+    fn default() -> Self {
+        SwitchTiming {
+            settle: Duration::from_millis(500),
+            poll: Duration::from_millis(250),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Switches `monitor` to `input`.
+///
+/// Returns the input source as last read back, or `None` if the monitor accepted the write but
+/// did not answer again before the timeout, as happens when it loses the signal of this computer.
+/// A failed write is only reported if the monitor never reports the requested input.
+pub fn switch(monitor: &Monitor, input: u8, timing: &SwitchTiming) -> Result<Option<Reply>> {
+    let deadline = Instant::now() + timing.timeout;
+    let written = match monitor.set_feature(Feature::InputSource, input as u32) {
+        Err(e) if e.kind() == ErrorKind::NotSupported => return Err(e),
+        written => written,
+    };
+    thread::sleep(timing.settle);
+    let mut last = None;
+    loop {
+        match monitor.get_feature(Feature::InputSource) {
+            // some monitors put garbage in the high byte
+            Ok(reply) if reply.current & 0xFF == input as u32 => return Ok(Some(reply)),
+            // others keep reporting the old input for a while
+            Ok(reply) => last = Some(reply),
+            Err(_) => (),
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        thread::sleep(timing.poll);
+    }
+    written.map(|_| last)
+}
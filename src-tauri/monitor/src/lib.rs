@@ -1,521 +1,515 @@
-#![allow(clippy::uninit_vec)]
-
-use std::collections::BTreeMap;
-use std::ffi::{c_void, OsString};
-use std::fmt::Write as _;
-use std::fs::OpenOptions;
-use std::mem::{size_of, take, transmute, MaybeUninit};
-use std::num::NonZeroUsize;
-use std::os::windows::ffi::{OsStrExt, OsStringExt};
-use std::os::windows::io::IntoRawHandle;
-use std::ptr;
-
-use once_cell::race::OnceNonZeroUsize;
-use wide::L;
-use windows::core::Interface as _;
-pub use windows::core::{Error, Result};
-use windows::core::{BSTR, PCWSTR};
-use windows::Win32::Devices::Display::{
-    DestroyPhysicalMonitor, GetNumberOfPhysicalMonitorsFromHMONITOR,
-    GetPhysicalMonitorsFromHMONITOR, GetVCPFeatureAndVCPFeatureReply, SetVCPFeature,
-    DISPLAYPOLICY_AC, DISPLAYPOLICY_DC, DISPLAY_BRIGHTNESS, IOCTL_VIDEO_QUERY_DISPLAY_BRIGHTNESS,
-    IOCTL_VIDEO_QUERY_SUPPORTED_BRIGHTNESS, IOCTL_VIDEO_SET_DISPLAY_BRIGHTNESS, PHYSICAL_MONITOR,
-};
-use windows::Win32::Foundation::{CloseHandle, BOOL, ERROR_NOT_SUPPORTED, HANDLE, LPARAM, RECT};
-use windows::Win32::Graphics::Gdi::{
-    EnumDisplayDevicesW, EnumDisplayMonitors, GetMonitorInfoW, DISPLAY_DEVICEW,
-    DISPLAY_DEVICE_ATTACHED_TO_DESKTOP, DISPLAY_DEVICE_MIRRORING_DRIVER, HDC, HMONITOR,
-    MONITORINFOEXW,
-};
-use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
-use windows::Win32::System::Ole::{
-    SafeArrayAccessData, SafeArrayGetLBound, SafeArrayGetUBound, SafeArrayUnaccessData,
-};
-use windows::Win32::System::Variant::{VariantClear, VARIANT, VT_ARRAY};
-use windows::Win32::System::Wmi::{
-    IWbemClassObject, IWbemLocator, IWbemServices, WbemLocator, WBEM_FLAG_CONNECT_USE_MAX_WAIT,
-    WBEM_FLAG_FORWARD_ONLY,
-};
-use windows::Win32::System::IO::DeviceIoControl;
+use std::cmp::Ordering;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use capabilities::Capabilities;
+use edid::Edid;
+use identity::MonitorId;
+use pacing::{Pacer, PacingPolicy, PacingStats};
+use quirks::Quirks;
+use serde::{Deserialize, Serialize};
+
+pub use error::{Error, ErrorKind, Result};
+
+pub mod backlight;
+pub mod capabilities;
+pub mod changes;
+pub mod ddc;
+pub mod drm;
+pub mod edid;
+pub mod error;
+pub mod i2c;
+pub mod identity;
+pub mod input;
+pub mod mock;
+pub mod pacing;
+pub mod quirks;
+pub mod simulate;
+pub mod trace;
+pub mod vcp;
+pub mod verify;
+pub mod watch;
+#[cfg(windows)]
+mod win32;
+pub mod worker;
+
+#[cfg(windows)]
+pub use win32::{init_com, Win32Backend};
 
-#[derive(Debug)]
-pub struct Monitor {
-    pub id: OsString,
-    hphysical: HANDLE,
-    hdevice: HANDLE,
+#[doc(hidden)]
+#[cfg(not(windows))]
+pub fn init_com() -> Result<()> {
+    Ok(())
 }
 
-impl Drop for Monitor {
-    fn drop(&mut self) {
-        if self.hphysical.0 != -1 {
-            unsafe { DestroyPhysicalMonitor(self.hphysical) }.unwrap();
-        }
-        if self.hdevice.0 != -1 {
-            unsafe { CloseHandle(self.hdevice) }.unwrap();
-        }
-    }
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interface {
+    DDCCI,
+    IOCTL,
+    Backlight,
 }
 
-fn pcwstr_to_osstring(s: &[u16]) -> OsString {
-    let len = s.iter().position(|&ch| ch == 0).unwrap_or(s.len());
-    OsString::from_wide(&s[..len])
+/// The kind of port a monitor is attached to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ConnectorType {
+    Vga,
+    Dvi,
+    Hdmi,
+    DisplayPort,
+    EmbeddedDisplayPort,
+    Lvds,
+    Dsi,
+    /// A built-in port of a kind the OS does not tell.
+    Internal,
+    Other,
 }
 
-fn get_monitor_ids() -> BTreeMap<OsString, OsString> {
-    fn get_display_device(
-        device_name: Option<&[u16]>,
-        device_num: u32,
-        flags: u32,
-    ) -> Option<DISPLAY_DEVICEW> {
-        let mut device = MaybeUninit::<DISPLAY_DEVICEW>::uninit();
-        unsafe { device.assume_init_mut() }.cb = size_of::<DISPLAY_DEVICEW>() as u32;
-        unsafe {
-            EnumDisplayDevicesW(
-                PCWSTR::from_raw(device_name.map_or(ptr::null(), |name| name.as_ptr())),
-                device_num,
-                device.as_mut_ptr(),
-                flags,
-            )
-        }
-        .as_bool()
-        .then(|| unsafe { device.assume_init() })
-    }
-
-    let mut monitor_ids = BTreeMap::new();
-    for adapter_idx in 0.. {
-        let Some(adapter) = get_display_device(None, adapter_idx, 0) else {
-            break;
-        };
-        for monitor_idx in 0.. {
-            let Some(monitor) = get_display_device(
-                Some(&adapter.DeviceName),
-                monitor_idx,
-                /* EDD_GET_DEVICE_INTERFACE_NAME */ 1,
-            ) else {
-                break;
-            };
-            if (monitor.StateFlags
-                & (DISPLAY_DEVICE_ATTACHED_TO_DESKTOP | DISPLAY_DEVICE_MIRRORING_DRIVER))
-                == DISPLAY_DEVICE_ATTACHED_TO_DESKTOP
-            {
-                let name = pcwstr_to_osstring(&monitor.DeviceName);
-                let id = pcwstr_to_osstring(&monitor.DeviceID);
-                monitor_ids.insert(name, id);
-            }
-        }
+impl ConnectorType {
+    /// Whether the port drives a built-in panel, whose brightness is controlled by the OS
+    /// rather than over DDC/CI.
+    pub fn is_builtin(&self) -> bool {
+        matches!(
+            self,
+            ConnectorType::EmbeddedDisplayPort
+                | ConnectorType::Lvds
+                | ConnectorType::Dsi
+                | ConnectorType::Internal
+        )
     }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reply {
+    pub current: u32,
+    pub maximum: u32,
+    pub source: Interface,
+}
 
-    monitor_ids
+/// A monitor control, identified by its MCCS VCP code.
+///
+/// The named variants are the controls the panel knows about; any other code is a `Vcp`. Features
+/// compare by VCP code, so `Feature::Vcp(0x10)` equals `Feature::Luminance`.
+#[derive(Debug, Copy, Clone)]
+pub enum Feature {
+    Luminance,
+    Contrast,
+    Brightness,
+    Volume,
+    PowerState,
+    InputSource,
+    Vcp(u8),
 }
 
-fn get_monitors_gdi(monitors: &mut Vec<Monitor>, monitor_ids: &mut BTreeMap<OsString, OsString>) {
-    fn get_monitor_info(hmonitor: HMONITOR) -> Option<MONITORINFOEXW> {
-        let mut info = MaybeUninit::<MONITORINFOEXW>::uninit();
-        unsafe { info.assume_init_mut() }.monitorInfo.cbSize = size_of::<MONITORINFOEXW>() as u32;
-        unsafe { GetMonitorInfoW(hmonitor, transmute(info.as_mut_ptr())) }
-            .as_bool()
-            .then(|| unsafe { info.assume_init() })
-    }
-
-    fn get_physical_monitors_from_hmonitor(hmonitor: HMONITOR) -> Vec<PHYSICAL_MONITOR> {
-        let mut num = 0;
-        let _ = unsafe { GetNumberOfPhysicalMonitorsFromHMONITOR(hmonitor, &mut num) };
-        (num != 0)
-            .then(|| {
-                let num = num as usize;
-                let mut v = Vec::with_capacity(num);
-                unsafe { v.set_len(num) };
-                unsafe { GetPhysicalMonitorsFromHMONITOR(hmonitor, v.as_mut_slice()) }.map(|()| v)
-            })
-            .transpose()
-            .ok()
-            .flatten()
-            .unwrap_or_default()
-    }
-
-    unsafe extern "system" fn monitor_enum_proc(
-        hmonitor: HMONITOR,
-        _hdc: HDC,
-        _rect: *mut RECT,
-        lparam: LPARAM,
-    ) -> BOOL {
-        let handles = &mut *(lparam.0 as *mut Vec<HMONITOR>);
-        handles.push(hmonitor);
-        true.into()
-    }
-
-    let mut handles = Vec::new();
-    unsafe {
-        EnumDisplayMonitors(
-            None,
-            None,
-            Some(monitor_enum_proc),
-            LPARAM(&mut handles as *mut Vec<HMONITOR> as isize),
-        )
-    };
+/// Names accepted by [`Feature::from_str`] besides the canonical ones.
+const FEATURE_ALIASES: [(&str, Feature); 6] = [
+    ("backlight", Feature::Brightness),
+    ("audiovolume", Feature::Volume),
+    ("power", Feature::PowerState),
+    ("powermode", Feature::PowerState),
+    ("dpms", Feature::PowerState),
+    ("source", Feature::InputSource),
+];
 
-    for hmonitor in handles {
-        let Some(info) = get_monitor_info(hmonitor) else {
-            continue;
-        };
-        let display_name = pcwstr_to_osstring(&info.szDevice);
-        let physical_monitors = get_physical_monitors_from_hmonitor(hmonitor);
-        for (i, phy) in physical_monitors.into_iter().enumerate() {
-            let mut monitor_name = display_name.clone();
-            write!(monitor_name, "\\Monitor{i}").unwrap();
-            let id = monitor_ids.remove(&monitor_name);
-            let Some(id) = id else {
-                debug_assert!(
-                    false,
-                    "the device of '{}' not found",
-                    monitor_name.to_string_lossy()
-                );
-                continue;
-            };
-            let hphysical = phy.hPhysicalMonitor;
-            let hdevice = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open(&id)
-                .map_or_else(
-                    |e| {
-                        debug_assert!(false, "failed to open device file handle: {e:?}");
-                        HANDLE(-1)
-                    },
-                    |f| HANDLE(f.into_raw_handle() as isize),
-                );
-            monitors.push(Monitor {
-                id,
-                hphysical,
-                hdevice,
-            });
+impl Feature {
+    pub const NAMED: [Feature; 6] = [
+        Feature::Luminance,
+        Feature::Contrast,
+        Feature::Brightness,
+        Feature::Volume,
+        Feature::PowerState,
+        Feature::InputSource,
+    ];
+
+    pub fn vcp_code(&self) -> u8 {
+        match self {
+            Feature::Luminance => 0x10,
+            Feature::Contrast => 0x12,
+            Feature::Brightness => 0x13,
+            Feature::Volume => 0x62,
+            Feature::PowerState => 0xD6,
+            Feature::InputSource => 0x60,
+            Feature::Vcp(code) => *code,
         }
     }
+
+    pub fn name(&self) -> Option<&'static str> {
+        Some(match self.normalize() {
+            Feature::Luminance => "luminance",
+            Feature::Contrast => "contrast",
+            Feature::Brightness => "brightness",
+            Feature::Volume => "volume",
+            Feature::PowerState => "powerstate",
+            Feature::InputSource => "input",
+            Feature::Vcp(_) => return None,
+        })
+    }
+
+    /// Returns the named variant for the same code, if there is one.
+    pub fn normalize(self) -> Feature {
+        Feature::from(self.vcp_code())
+    }
+
+    /// Returns the MCCS description of the code, if it is a standard one.
+    pub fn describe(&self) -> Option<&'static vcp::VcpCode> {
+        vcp::lookup(self.vcp_code())
+    }
+
+    /// Returns the MCCS name of `value`, e.g. `Standby` for a power state of 2.
+    pub fn value_name(&self, value: u32) -> Option<&'static str> {
+        vcp::value_name(self.vcp_code(), value)
+    }
 }
 
-pub fn get_monitors() -> Vec<Monitor> {
-    let mut monitors = Vec::new();
-    let mut monitor_ids = get_monitor_ids();
-    get_monitors_gdi(&mut monitors, &mut monitor_ids);
-    debug_assert!(
-        monitor_ids.is_empty(),
-        "cannot get interfaces for some display devices: {monitor_ids:?}"
-    );
-    monitors
+impl From<u8> for Feature {
+    fn from(code: u8) -> Self {
+        Feature::NAMED
+            .into_iter()
+            .find(|feature| feature.vcp_code() == code)
+            .unwrap_or(Feature::Vcp(code))
+    }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum Interface {
-    DDCCI,
-    IOCTL,
+impl PartialEq for Feature {
+    fn eq(&self, other: &Self) -> bool {
+        self.vcp_code() == other.vcp_code()
+    }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct Reply {
-    pub current: u32,
-    pub maximum: u32,
-    pub source: Interface,
+impl Eq for Feature {}
+
+impl Hash for Feature {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.vcp_code().hash(state)
+    }
 }
 
-fn ddcci_get_vcp(hphysical: HANDLE, code: u8) -> Result<Reply> {
-    let mut reply = Reply {
-        current: 0,
-        maximum: 0,
-        source: Interface::DDCCI,
-    };
-    if unsafe {
-        GetVCPFeatureAndVCPFeatureReply(
-            hphysical,
-            code,
-            None,
-            &mut reply.current,
-            Some(&mut reply.maximum),
-        )
-    } == 0
-    {
-        Err(Error::from_win32())
-    } else {
-        Ok(reply)
+impl PartialOrd for Feature {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-fn ddcci_set_vcp(hpysical: HANDLE, code: u8, value: u32) -> Result<()> {
-    if unsafe { SetVCPFeature(hpysical, code, value) } == 0 {
-        Err(Error::from_win32())
-    } else {
-        Ok(())
+impl Ord for Feature {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.vcp_code().cmp(&other.vcp_code())
     }
 }
 
-// ioctl functions are copied from the "brightness" crate
-
-fn ioctl_query_supported_brightness(hdevice: HANDLE) -> Result<Vec<u8>> {
-    let mut bytes_returned = 0;
-    let mut out_buffer = Vec::<u8>::with_capacity(256);
-    unsafe {
-        DeviceIoControl(
-            hdevice,
-            IOCTL_VIDEO_QUERY_SUPPORTED_BRIGHTNESS,
-            None,
-            0,
-            Some(out_buffer.as_mut_ptr() as *mut c_void),
-            out_buffer.capacity() as u32,
-            Some(&mut bytes_returned),
-            None,
-        )
-    }?;
-    unsafe { out_buffer.set_len(bytes_returned as usize) };
-    Ok(out_buffer)
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{:#04x}", self.vcp_code()),
+        }
+    }
 }
 
-fn ioctl_query_display_brightness(hdevice: HANDLE) -> Result<u8> {
-    let mut display_brightness = MaybeUninit::<DISPLAY_BRIGHTNESS>::uninit();
-    unsafe {
-        DeviceIoControl(
-            hdevice,
-            IOCTL_VIDEO_QUERY_DISPLAY_BRIGHTNESS,
-            None,
-            0,
-            Some(display_brightness.as_mut_ptr() as *mut c_void),
-            size_of::<DISPLAY_BRIGHTNESS>() as u32,
-            None,
-            None,
-        )
-    }?;
-    let display_brightness = unsafe { display_brightness.assume_init() };
-    Ok(match display_brightness.ucDisplayPolicy as u32 {
-        DISPLAYPOLICY_AC => display_brightness.ucACBrightness,
-        DISPLAYPOLICY_DC => display_brightness.ucDCBrightness,
-        _ => unreachable!(),
-    })
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFeatureError(String);
+
+impl fmt::Display for ParseFeatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid feature name: '{}'", self.0)
+    }
 }
 
-fn ioctl_set_display_brightness(hdevice: HANDLE, value: u8) -> Result<()> {
-    let mut display_brightness = DISPLAY_BRIGHTNESS {
-        ucACBrightness: value,
-        ucDCBrightness: value,
-        ucDisplayPolicy: 3, // DISPLAYPOLICY_BOTH
-    };
-    unsafe {
-        DeviceIoControl(
-            hdevice,
-            IOCTL_VIDEO_SET_DISPLAY_BRIGHTNESS,
-            Some(&mut display_brightness as *mut DISPLAY_BRIGHTNESS as *mut c_void),
-            size_of::<DISPLAY_BRIGHTNESS>() as u32,
-            None,
-            0,
-            None,
-            None,
-        )
+impl std::error::Error for ParseFeatureError {}
+
+impl FromStr for Feature {
+    type Err = ParseFeatureError;
+
+    /// Parses a name or alias, ignoring case, spaces, dashes and underscores, an MCCS name such
+    /// as `input source`, or a hex code such as `0x60`.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let err = || ParseFeatureError(s.to_owned());
+        let key: String = s
+            .chars()
+            .filter(|ch| !matches!(ch, ' ' | '-' | '_'))
+            .map(|ch| ch.to_ascii_lowercase())
+            .collect();
+        if let Some(hex) = key.strip_prefix("0x") {
+            return u8::from_str_radix(hex, 16)
+                .map(Feature::from)
+                .map_err(|_| err());
+        }
+        Feature::NAMED
+            .into_iter()
+            .find(|feature| feature.name() == Some(key.as_str()))
+            .or_else(|| {
+                FEATURE_ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == key)
+                    .map(|(_, feature)| *feature)
+            })
+            .or_else(|| {
+                let vcp = vcp::find(&key).filter(|_| key.chars().all(char::is_alphanumeric))?;
+                Some(Feature::from(vcp.code))
+            })
+            .ok_or_else(err)
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum Feature {
-    Luminance,
-    Contrast,
-    Brightness,
-    Volume,
-    PowerState,
+/// A source of monitors, e.g. the Win32 display APIs or an in-memory mock.
+pub trait MonitorBackend: fmt::Debug + Send + Sync {
+    fn enumerate(&self) -> Vec<Monitor>;
+    /// Returns a value that changes whenever monitors may have been connected or disconnected,
+    /// and is much cheaper than enumerating them; `None` if the backend cannot tell.
+    fn generation(&self) -> Option<u64>;
 }
 
-impl Feature {
-    fn vcp_code(&self) -> u8 {
-        match self {
-            Feature::Luminance => 0x10,
-            Feature::Contrast => 0x12,
-            Feature::Brightness => 0x13,
-            Feature::Volume => 0x62,
-            Feature::PowerState => 0xD6,
-        }
+/// The operations a backend implements for each monitor it enumerates.
+pub trait MonitorDevice: fmt::Debug + Send + Sync {
+    fn get_feature(&self, feature: Feature) -> Result<Reply>;
+    fn set_feature(&self, feature: Feature, value: u32) -> Result<()>;
+    fn get_user_friendly_name(&self) -> Result<Option<OsString>>;
+    /// Returns the raw MCCS capabilities string.
+    fn get_capabilities_string(&self) -> Result<String>;
+    /// Returns the raw EDID, starting with the base block.
+    fn get_edid(&self) -> Result<Vec<u8>>;
+    /// Returns how to pace the commands sent to the monitor; by default they are not paced.
+    fn pacing(&self) -> PacingPolicy {
+        PacingPolicy::none()
+    }
+    /// Names the bus the commands to the monitor travel on, for the monitors given the same name
+    /// to take turns on it; by default the monitor has a bus of its own. Which monitors share a
+    /// hub cannot be told on real hardware: Win32 says nothing of the hubs between a monitor and
+    /// the GPU, and Linux gives each MST port an I2C adapter of its own. Only simulations name a
+    /// shared bus.
+    fn bus(&self) -> Option<String> {
+        None
     }
 }
 
+/// The capabilities reported for panels driven through a backlight interface.
+pub(crate) const BACKLIGHT_CAPABILITIES: &str = "(prot(monitor)type(LCD)vcp(10))";
+
+/// Chains two backends, listing the monitors of the first before those of the second.
+impl<A: MonitorBackend, B: MonitorBackend> MonitorBackend for (A, B) {
+    fn enumerate(&self) -> Vec<Monitor> {
+        let mut monitors = self.0.enumerate();
+        monitors.extend(self.1.enumerate());
+        monitors
+    }
+
+    fn generation(&self) -> Option<u64> {
+        Some(watch::hash(&(self.0.generation()?, self.1.generation()?)))
+    }
+}
+
+#[derive(Debug)]
+pub struct Monitor {
+    /// The path the OS gives to the monitor, which changes with the port it is attached to.
+    pub id: OsString,
+    /// The identity derived from the EDID; see [`identity`]. It is the OS path until
+    /// [`identity::identify`] runs, as it does in [`enumerate`].
+    pub identity: MonitorId,
+    /// The kind of port, if the backend knows it.
+    pub connector: Option<ConnectorType>,
+    device: Box<dyn MonitorDevice>,
+    pacer: Pacer,
+    quirks: Quirks,
+}
+
 impl Monitor {
-    fn is_builtin(&self) -> bool {
-        self.id.as_encoded_bytes().starts_with(b"\\\\?\\LCD")
+    pub fn new(id: OsString, device: Box<dyn MonitorDevice>) -> Monitor {
+        Monitor {
+            identity: MonitorId::from(id.to_string_lossy().into_owned()),
+            id,
+            connector: None,
+            pacer: Pacer::new(device.pacing()),
+            device,
+            quirks: Quirks::default(),
+        }
+    }
+
+    pub fn with_connector(mut self, connector: ConnectorType) -> Monitor {
+        self.connector = Some(connector);
+        self
+    }
+
+    /// Whether the monitor is a built-in panel; monitors of an unknown port are assumed external.
+    pub fn is_builtin(&self) -> bool {
+        self.connector
+            .is_some_and(|connector| connector.is_builtin())
+    }
+
+    /// Whether `id` is either the OS path or the identity of the monitor.
+    pub fn matches(&self, id: &str) -> bool {
+        self.id == id || self.identity.as_str() == id
+    }
+
+    /// Replaces the quirks of the monitor, and with them its pacing; see [`quirks`].
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.pacer = Pacer::new(quirks.pacing.apply(self.device.pacing()));
+        self.quirks = quirks;
+    }
+
+    pub fn quirks(&self) -> &Quirks {
+        &self.quirks
+    }
+
+    /// The value of [`Feature::PowerState`] that turns the monitor off, if it is known to differ
+    /// from what the user configured.
+    pub fn power_off_value(&self) -> Option<u32> {
+        self.quirks.power_off
+    }
+
+    fn check_enabled(&self, feature: Feature) -> Result<u8> {
+        let code = feature.vcp_code();
+        if self.quirks.disabled.contains(&code) {
+            return Err(Error::from(ErrorKind::NotSupported));
+        }
+        Ok(code)
     }
 
     pub fn get_feature(&self, feature: Feature) -> Result<Reply> {
-        if self.is_builtin() {
-            if feature == Feature::Luminance {
-                ioctl_query_display_brightness(self.hdevice).map(|value| Reply {
-                    current: value as u32,
-                    maximum: 100,
-                    source: Interface::IOCTL,
-                })
-            } else {
-                Err(ERROR_NOT_SUPPORTED.into())
-            }
-        } else {
-            ddcci_get_vcp(self.hphysical, feature.vcp_code())
+        let code = self.check_enabled(feature)?;
+        let mut reply = self.pacer.run(|| self.device.get_feature(feature))?;
+        reply.current = self.quirks.read_value(code, reply.current);
+        if let Some(maximum) = self.quirks.maximum.get(&code) {
+            reply.maximum = *maximum;
         }
+        Ok(reply)
     }
 
     pub fn set_feature(&self, feature: Feature, value: u32) -> Result<()> {
-        if self.is_builtin() {
-            if feature == Feature::Luminance {
-                ioctl_query_supported_brightness(self.hdevice).and_then(|levels| {
-                    let value = levels
-                        .iter()
-                        .min_by_key(|&level| value.abs_diff(*level as u32))
-                        .copied()
-                        .unwrap_or(value as u8);
-                    ioctl_set_display_brightness(self.hdevice, value)
-                })
-            } else {
-                Err(ERROR_NOT_SUPPORTED.into())
-            }
-        } else {
-            ddcci_set_vcp(self.hphysical, feature.vcp_code(), value)
-        }
+        let code = self.check_enabled(feature)?;
+        let value = self.quirks.write_value(code, value);
+        self.pacer.run(|| self.device.set_feature(feature, value))
     }
-}
 
-impl Monitor {
-    fn get_wmi_instance_name(&self) -> Vec<u16> {
-        let mut id: Vec<u16> = self.id.encode_wide().collect();
-        if id.strip_prefix(&L!("\\\\?\\")).is_some() {
-            id.drain(..4);
-        } else {
-            debug_assert!(false, "id not starts with '\\\\?\\'");
+    /// Reads which controls changed on the monitor itself; see [`changes::changes`].
+    pub fn changes(&self) -> Result<changes::Changes> {
+        changes::changes(self)
+    }
+
+    /// Sets `feature` and reads it back until it matches; see [`verify::set`].
+    pub fn set_feature_verified(&self, feature: Feature, value: u32) -> Result<verify::Outcome> {
+        verify::set(self, feature, value, &verify::VerifyTiming::default())
+    }
+
+    /// Returns the bus shared with other monitors, if known; see [`MonitorDevice::bus`].
+    pub fn bus(&self) -> Option<String> {
+        self.device.bus()
+    }
+
+    /// Returns the delay learned between commands and the failures seen so far.
+    pub fn pacing_stats(&self) -> PacingStats {
+        self.pacer.stats()
+    }
+
+    /// Returns the name reported by the OS, or else the display name in the EDID.
+    pub fn get_user_friendly_name(&self) -> Result<Option<OsString>> {
+        let name = self.device.get_user_friendly_name();
+        if let Ok(Some(name)) = name {
+            return Ok(Some(name));
         }
-        let mut last_hash = 0;
-        for (i, ch) in id.iter_mut().enumerate() {
-            if *ch == L!('#') {
-                *ch = L!('\\');
-                last_hash = i;
-            }
+        match self.get_edid() {
+            Ok(Edid {
+                name: Some(name), ..
+            }) => Ok(Some(name.into())),
+            _ => name,
         }
-        debug_assert_ne!(last_hash, 0);
-        debug_assert_eq!(id.len() - last_hash, 39);
-        id.truncate(last_hash);
-        id.push(L!('_'));
-        id.push(L!('0'));
-        id
-    }
-
-    fn get_wmi_instance(&self, class: &[u16]) -> Result<Option<IWbemClassObject>> {
-        let mut query = Vec::from(L!("SELECT * FROM "));
-        query.extend_from_slice(class);
-        query.extend(L!(" WHERE InstanceName=\""));
-        let instance_name = self.get_wmi_instance_name();
-        query.extend(instance_name.into_iter().flat_map(|ch| match ch {
-            L!('\\') => [ch, ch].into_iter().take(2),
-            L!('"') => [L!('\\'), ch].into_iter().take(2),
-            ch => [ch, 0].into_iter().take(1),
-        }));
-        query.push(L!('"'));
-        query_wmi(&query)
     }
 
-    pub fn get_user_friendly_name(&self) -> Result<Option<OsString>> {
-        let Some(instance) = self.get_wmi_instance(&L!("WmiMonitorID"))? else {
-            return Ok(None);
-        };
-        let mut variant = MaybeUninit::<VARIANT>::uninit();
-        unsafe {
-            instance.Get(
-                PCWSTR::from_raw(L!("UserFriendlyName\0").as_ptr()),
-                0,
-                variant.as_mut_ptr(),
-                None,
-                None,
-            )
-        }?;
-        let mut variant = unsafe { variant.assume_init() };
-        let s = ((unsafe { &variant.Anonymous.Anonymous }.vt.0 & VT_ARRAY.0) != 0)
-            .then(|| {
-                let array = unsafe { variant.Anonymous.Anonymous.Anonymous.parray };
-                let mut data = ptr::null_mut();
-                let l = unsafe { SafeArrayGetLBound(array, 1) }?;
-                let r = unsafe { SafeArrayGetUBound(array, 1) }?;
-                unsafe { SafeArrayAccessData(array, &mut data) }?;
-                let buf =
-                    &unsafe { std::slice::from_raw_parts(data as *const u32, r as usize + 1) }
-                        [l as usize..];
-                let buf: Vec<_> = buf
-                    .iter()
-                    .take_while(|&ch| *ch != 0)
-                    .map(|&ch| ch as u16)
-                    .collect();
-                let s = OsString::from_wide(&buf);
-                unsafe { SafeArrayUnaccessData(array) }?;
-                Ok(s)
-            })
-            .transpose();
-        unsafe { VariantClear(&mut variant) }?;
-        s
+    pub fn get_edid(&self) -> Result<Edid> {
+        Ok(Edid::parse(&self.device.get_edid()?)?)
+    }
+
+    pub fn get_capabilities(&self) -> Result<Capabilities> {
+        let mut caps = self
+            .pacer
+            .run(|| self.device.get_capabilities_string())
+            .map(|s| Capabilities::parse(&s))?;
+        caps.vcp
+            .retain(|code, _| !self.quirks.disabled.contains(code));
+        Ok(caps)
+    }
+
+    /// Returns the inputs the monitor declares in its capabilities string.
+    pub fn get_inputs(&self) -> Result<Vec<input::Input>> {
+        Ok(input::inputs(&self.get_capabilities()?))
+    }
+
+    /// Switches to `input` and waits for the monitor to answer again; see [`input::switch`].
+    pub fn set_input(&self, input: u8) -> Result<Option<Reply>> {
+        input::switch(self, input, &input::SwitchTiming::default())
     }
 }
 
-#[doc(hidden)]
-pub fn init_com() -> Result<()> {
-    use windows::Win32::System::Com::{
-        CoInitializeSecurity, EOAC_NONE, RPC_C_AUTHN_LEVEL_DEFAULT, RPC_C_IMP_LEVEL_IMPERSONATE,
+/// Returns the backend of this platform, or the demo mock backend if `MONITOR_BACKEND=mock`.
+///
+/// `MONITOR_REPLAY=<file>` replaces it with the monitors of a trace, and `MONITOR_TRACE=<file>`
+/// appends the commands sent through it to a trace; see [`trace`]. Fails if the file cannot be
+/// opened.
+pub fn default_backend() -> Result<Box<dyn MonitorBackend>> {
+    if let Some(path) = env::var_os("MONITOR_REPLAY") {
+        let backend = trace::ReplayBackend::open(&path)
+            .map_err(|e| trace_error("failed to read trace", &path, e))?;
+        return Ok(Box::new(backend));
+    }
+    let backend = platform_backend();
+    match env::var_os("MONITOR_TRACE") {
+        Some(path) => {
+            let file =
+                trace::append(&path).map_err(|e| trace_error("failed to open trace", &path, e))?;
+            Ok(Box::new(trace::TraceBackend::new(backend, file)))
+        }
+        None => Ok(backend),
+    }
+}
+
+fn trace_error(what: &str, path: &OsString, error: std::io::Error) -> Error {
+    let message = format!("{what} {}: {error}", path.to_string_lossy());
+    Error::from(std::io::Error::new(error.kind(), message))
+}
+
+/// Removes `--simulate <file>` from `args` and returns the virtual monitors described in the
+/// file, or else [`default_backend`]; see [`simulate`].
+pub fn backend_from_args(args: &mut Vec<OsString>) -> Result<Box<dyn MonitorBackend>> {
+    let Some(i) = args.iter().position(|arg| arg == "--simulate") else {
+        return default_backend();
     };
-    use windows::Win32::System::Ole::OleInitialize;
-    unsafe { OleInitialize(None) }?;
-    unsafe {
-        CoInitializeSecurity(
-            None,
-            -1,
-            None,
-            None,
-            RPC_C_AUTHN_LEVEL_DEFAULT,
-            RPC_C_IMP_LEVEL_IMPERSONATE,
-            None,
-            EOAC_NONE,
-            None,
-        )
-    }?;
-    Ok(())
+    if i + 1 >= args.len() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "expected a file after --simulate",
+        ));
+    }
+    let path = args.drain(i..i + 2).nth(1).unwrap();
+    Ok(Box::new(simulate::load(path)?))
 }
 
-static WMI_SERVICES: OnceNonZeroUsize = OnceNonZeroUsize::new();
-
-fn create_wmi_services() -> Result<IWbemServices> {
-    let locator: IWbemLocator =
-        unsafe { CoCreateInstance(&WbemLocator, None, CLSCTX_INPROC_SERVER) }?;
-    let resource = BSTR::from_wide(&L!("root\\WMI"))?;
-    unsafe {
-        locator.ConnectServer(
-            &resource,
-            None,
-            None,
-            None,
-            WBEM_FLAG_CONNECT_USE_MAX_WAIT.0,
-            None,
-            None,
-        )
+fn platform_backend() -> Box<dyn MonitorBackend> {
+    if env::var_os("MONITOR_BACKEND").is_some_and(|backend| backend == "mock") {
+        return Box::new(mock::MockBackend::demo());
     }
+    #[cfg(windows)]
+    return Box::new(Win32Backend);
+    #[cfg(target_os = "linux")]
+    return Box::new((
+        backlight::BacklightBackend::default(),
+        i2c::I2cBackend::default(),
+    ));
+    #[cfg(not(any(windows, target_os = "linux")))]
+    return Box::new(mock::MockBackend::new());
 }
 
-fn get_wmi_services() -> Result<IWbemServices> {
-    let services = WMI_SERVICES
-        .get_or_try_init(|| -> Result<NonZeroUsize> {
-            let services = create_wmi_services()?;
-            let ptr = services.into_raw() as usize;
-            Ok(NonZeroUsize::try_from(ptr).unwrap())
-        })?
-        .get() as *mut c_void;
-    Ok(unsafe { IWbemServices::from_raw_borrowed(&services) }
-        .unwrap()
-        .clone())
+/// Enumerates the monitors of `backend`, assigns their identities and applies their quirks.
+pub fn enumerate(backend: &dyn MonitorBackend) -> Vec<Monitor> {
+    let mut monitors = backend.enumerate();
+    let edids: Vec<_> = monitors
+        .iter()
+        .map(|monitor| monitor.get_edid().ok())
+        .collect();
+    identity::identify_with(&mut monitors, &edids);
+    quirks::apply(&mut monitors, &edids, quirks::database());
+    monitors
 }
 
-fn query_wmi(query: &[u16]) -> Result<Option<IWbemClassObject>> {
-    let services = get_wmi_services()?;
-    let enumerator = unsafe {
-        services.ExecQuery(
-            &BSTR::from_wide(&L!("WQL"))?,
-            &BSTR::from_wide(query)?,
-            WBEM_FLAG_FORWARD_ONLY,
-            None,
-        )
-    }?;
-    let mut objects = [None; 1];
-    let mut returned = 0;
-    let _ = unsafe { enumerator.Next(1000, &mut objects, &mut returned) };
-    Ok(take(&mut objects[0]))
+pub fn get_monitors() -> Result<Vec<Monitor>> {
+    Ok(enumerate(&*default_backend()?))
 }
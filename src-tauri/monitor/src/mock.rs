@@ -0,0 +1,385 @@
+//! An in-memory backend with scripted values and failures, for running without real monitors.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::ffi::OsString;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::changes::{ACTIVE_CONTROL, NEW_CONTROL_VALUE};
+use crate::edid::Edid;
+use crate::pacing::PacingPolicy;
+use crate::watch;
+use crate::{
+    ConnectorType, Error, ErrorKind, Feature, Interface, Monitor, MonitorBackend, MonitorDevice,
+    Reply, Result,
+};
+
+#[derive(Debug, Default, Clone)]
+pub struct MockBackend {
+    monitors: Vec<MockMonitor>,
+}
+
+impl MockBackend {
+    pub fn new() -> MockBackend {
+        MockBackend::default()
+    }
+
+    pub fn with_monitor(mut self, monitor: MockMonitor) -> MockBackend {
+        self.monitors.push(monitor);
+        self
+    }
+
+    /// A built-in panel and an external monitor, used by `MONITOR_BACKEND=mock`.
+    pub fn demo() -> MockBackend {
+        let ddcci = |current| Reply {
+            current,
+            maximum: 100,
+            source: Interface::DDCCI,
+        };
+        MockBackend::new()
+            .with_monitor(
+                MockMonitor::new("MOCK#BUILTIN#0")
+                    .name("Built-in Display")
+                    .connector(ConnectorType::EmbeddedDisplayPort)
+                    .feature(
+                        Feature::Luminance,
+                        Reply {
+                            current: 80,
+                            maximum: 100,
+                            source: Interface::IOCTL,
+                        },
+                    ),
+            )
+            .with_monitor(
+                MockMonitor::new("MOCK#EXTERNAL#1")
+                    .name("Mock Monitor")
+                    .connector(ConnectorType::DisplayPort)
+                    .capabilities(
+                        "(prot(monitor)type(LCD)model(MOCK)cmds(01 02 03 0C F3)\
+                         vcp(10 12 60(0F 11 12) 62 D6(01 04 05))mccs_ver(2.2))",
+                    )
+                    .edid(
+                        Edid {
+                            manufacturer: "MCK".to_owned(),
+                            product_code: 0x0001,
+                            serial_number: 1,
+                            year: 2024,
+                            version: (1, 4),
+                            size: Some((60, 34)),
+                            name: Some("Mock Monitor".to_owned()),
+                            ..Default::default()
+                        }
+                        .encode(),
+                    )
+                    .feature(Feature::Luminance, ddcci(50))
+                    .feature(Feature::Contrast, ddcci(50))
+                    .feature(Feature::Volume, ddcci(30))
+                    .feature(
+                        Feature::PowerState,
+                        Reply {
+                            current: 1,
+                            maximum: 5,
+                            source: Interface::DDCCI,
+                        },
+                    )
+                    .feature(
+                        Feature::InputSource,
+                        Reply {
+                            current: 0x0F,
+                            maximum: 0x12,
+                            source: Interface::DDCCI,
+                        },
+                    ),
+            )
+    }
+}
+
+impl MonitorBackend for MockBackend {
+    fn enumerate(&self) -> Vec<Monitor> {
+        self.monitors
+            .iter()
+            .map(|monitor| {
+                let mut enumerated = Monitor::new(monitor.id.clone(), Box::new(monitor.clone()));
+                enumerated.connector = monitor.state.lock().unwrap().connector;
+                enumerated
+            })
+            .collect()
+    }
+
+    fn generation(&self) -> Option<u64> {
+        let ids: Vec<_> = self.monitors.iter().map(|monitor| &monitor.id).collect();
+        Some(watch::hash(&ids))
+    }
+}
+
+/// A scripted monitor. Clones share the same state, so a handle kept by a test observes the
+/// writes made through the enumerated [`Monitor`].
+#[derive(Debug, Clone)]
+pub struct MockMonitor {
+    id: OsString,
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    name: Option<OsString>,
+    connector: Option<ConnectorType>,
+    capabilities: Option<String>,
+    edid: Option<Vec<u8>>,
+    features: BTreeMap<u8, Reply>,
+    failures: BTreeMap<u8, VecDeque<ErrorKind>>,
+    silent_after_write: usize,
+    silence: usize,
+    pacing: Option<PacingPolicy>,
+    bus: Option<String>,
+    latency: Duration,
+    failure_rate: f64,
+    /// The state of the xorshift generator drawing random failures.
+    seed: u64,
+    ignores_writes: bool,
+    clamps: BTreeMap<u8, (u32, u32)>,
+    reports_changes: bool,
+    /// The codes changed from the OSD and not read from Active Control yet.
+    changed: VecDeque<u8>,
+}
+
+impl MockMonitor {
+    pub fn new(id: impl Into<OsString>) -> MockMonitor {
+        MockMonitor {
+            id: id.into(),
+            state: Default::default(),
+        }
+    }
+
+    pub fn name(self, name: impl Into<OsString>) -> MockMonitor {
+        self.state.lock().unwrap().name = Some(name.into());
+        self
+    }
+
+    pub fn connector(self, connector: ConnectorType) -> MockMonitor {
+        self.state.lock().unwrap().connector = Some(connector);
+        self
+    }
+
+    /// Sets the capabilities string; by default it lists the declared features.
+    pub fn capabilities(self, capabilities: impl Into<String>) -> MockMonitor {
+        self.state.lock().unwrap().capabilities = Some(capabilities.into());
+        self
+    }
+
+    /// Sets the EDID; without one, reading it fails with [`ErrorKind::NotSupported`].
+    pub fn edid(self, edid: impl Into<Vec<u8>>) -> MockMonitor {
+        self.state.lock().unwrap().edid = Some(edid.into());
+        self
+    }
+
+    /// Declares a supported feature; features not declared fail with [`ErrorKind::NotSupported`].
+    pub fn feature(self, feature: Feature, reply: Reply) -> MockMonitor {
+        self.state
+            .lock()
+            .unwrap()
+            .features
+            .insert(feature.vcp_code(), reply);
+        self
+    }
+
+    /// Makes the next `times` accesses to `feature` fail with `kind`.
+    pub fn fail(self, feature: Feature, kind: ErrorKind, times: usize) -> MockMonitor {
+        self.state
+            .lock()
+            .unwrap()
+            .failures
+            .entry(feature.vcp_code())
+            .or_default()
+            .extend((0..times).map(|_| kind));
+        self
+    }
+
+    /// Makes every access fail with [`ErrorKind::Timeout`] for the next `times` accesses after
+    /// each write, like monitors that stop answering while they switch inputs.
+    pub fn silent_after_write(self, times: usize) -> MockMonitor {
+        self.state.lock().unwrap().silent_after_write = times;
+        self
+    }
+
+    /// Paces the commands sent to the monitor; by default they are not paced.
+    pub fn pacing(self, pacing: PacingPolicy) -> MockMonitor {
+        self.state.lock().unwrap().pacing = Some(pacing);
+        self
+    }
+
+    /// Puts the monitor on `bus`, which monitors given the same name share.
+    pub fn bus(self, bus: impl Into<String>) -> MockMonitor {
+        self.state.lock().unwrap().bus = Some(bus.into());
+        self
+    }
+
+    /// Delays every access by `latency`, like the round trip of a DDC/CI command.
+    pub fn latency(self, latency: Duration) -> MockMonitor {
+        self.state.lock().unwrap().latency = latency;
+        self
+    }
+
+    /// Makes each access fail with [`ErrorKind::NoReply`] with probability `rate`. The failures
+    /// are drawn from `seed`, so that a run can be repeated.
+    pub fn failure_rate(self, rate: f64, seed: u64) -> MockMonitor {
+        let mut state = self.state.lock().unwrap();
+        state.failure_rate = rate;
+        // xorshift never leaves zero
+        state.seed = seed.max(1);
+        drop(state);
+        self
+    }
+
+    /// Acknowledges writes without applying them, like monitors with a locked OSD.
+    pub fn ignore_writes(self) -> MockMonitor {
+        self.state.lock().unwrap().ignores_writes = true;
+        self
+    }
+
+    /// Clamps the values written to `feature` to `min..=max`, which may be narrower than the
+    /// maximum the monitor reports.
+    pub fn clamp(self, feature: Feature, min: u32, max: u32) -> MockMonitor {
+        self.state
+            .lock()
+            .unwrap()
+            .clamps
+            .insert(feature.vcp_code(), (min, max));
+        self
+    }
+
+    /// Reports the changes made with [`MockMonitor::change`] through New Control Value and
+    /// Active Control; see [`crate::changes`].
+    pub fn report_changes(self) -> MockMonitor {
+        self.state.lock().unwrap().reports_changes = true;
+        self
+    }
+
+    /// Changes `feature` as the user does with the OSD of the monitor.
+    pub fn change(&self, feature: Feature, value: u32) {
+        let mut state = self.state.lock().unwrap();
+        let code = feature.vcp_code();
+        if let Some(reply) = state.features.get_mut(&code) {
+            reply.current = value;
+        }
+        if state.reports_changes && !state.changed.contains(&code) {
+            state.changed.push_back(code);
+        }
+    }
+
+    /// Returns the current value of `feature` without consuming scripted failures.
+    pub fn value(&self, feature: Feature) -> Option<Reply> {
+        self.state
+            .lock()
+            .unwrap()
+            .features
+            .get(&feature.vcp_code())
+            .copied()
+    }
+}
+
+impl MockState {
+    fn random(&mut self) -> f64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        (self.seed >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Answers New Control Value and Active Control, if the monitor reports changes.
+    fn report(&mut self, code: u8) -> Option<Reply> {
+        let current = match code {
+            _ if !self.reports_changes => return None,
+            NEW_CONTROL_VALUE if self.changed.is_empty() => 0x01,
+            NEW_CONTROL_VALUE => 0x02,
+            ACTIVE_CONTROL => self.changed.pop_front().map_or(0, u32::from),
+            _ => return None,
+        };
+        Some(Reply {
+            current,
+            maximum: 0xFF,
+            source: Interface::DDCCI,
+        })
+    }
+
+    fn access(&mut self, code: u8) -> Result<&mut Reply> {
+        if !self.latency.is_zero() {
+            thread::sleep(self.latency);
+        }
+        if self.failure_rate > 0.0 && self.random() < self.failure_rate {
+            return Err(Error::from(ErrorKind::NoReply));
+        }
+        if self.silence > 0 {
+            self.silence -= 1;
+            return Err(Error::from(ErrorKind::Timeout));
+        }
+        if let Some(kind) = self.failures.get_mut(&code).and_then(VecDeque::pop_front) {
+            return Err(Error::from(kind));
+        }
+        self.features
+            .get_mut(&code)
+            .ok_or_else(|| Error::from(ErrorKind::NotSupported))
+    }
+}
+
+impl MonitorDevice for MockMonitor {
+    fn get_feature(&self, feature: Feature) -> Result<Reply> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(reply) = state.report(feature.vcp_code()) {
+            return Ok(reply);
+        }
+        state.access(feature.vcp_code()).map(|reply| *reply)
+    }
+
+    fn set_feature(&self, feature: Feature, value: u32) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let code = feature.vcp_code();
+        if state.reports_changes && code == NEW_CONTROL_VALUE {
+            return Ok(());
+        }
+        let (min, max) = state.clamps.get(&code).copied().unwrap_or((0, u32::MAX));
+        let ignores_writes = state.ignores_writes;
+        let reply = state.access(code)?;
+        if !ignores_writes {
+            reply.current = value.clamp(min, max).min(reply.maximum);
+        }
+        state.silence = state.silent_after_write;
+        Ok(())
+    }
+
+    fn get_user_friendly_name(&self) -> Result<Option<OsString>> {
+        Ok(self.state.lock().unwrap().name.clone())
+    }
+
+    fn get_capabilities_string(&self) -> Result<String> {
+        let state = self.state.lock().unwrap();
+        if let Some(capabilities) = &state.capabilities {
+            return Ok(capabilities.clone());
+        }
+        let codes: Vec<_> = state
+            .features
+            .keys()
+            .map(|code| format!("{code:02X}"))
+            .collect();
+        Ok(format!("(prot(monitor)vcp({}))", codes.join(" ")))
+    }
+
+    fn get_edid(&self) -> Result<Vec<u8>> {
+        self.state
+            .lock()
+            .unwrap()
+            .edid
+            .clone()
+            .ok_or_else(|| Error::from(ErrorKind::NotSupported))
+    }
+
+    fn pacing(&self) -> PacingPolicy {
+        let state = self.state.lock().unwrap();
+        state.pacing.clone().unwrap_or_else(PacingPolicy::none)
+    }
+
+    fn bus(&self) -> Option<String> {
+        self.state.lock().unwrap().bus.clone()
+    }
+}
@@ -0,0 +1,182 @@
+//! Pacing of DDC/CI commands and retries of failed ones.
+//!
+//! Monitors ignore or garble commands that follow the previous one too closely, and how close is
+//! too close varies from 50 ms to well over 200 ms between models. A [`Pacer`] therefore learns
+//! the delay of each monitor: it starts from a conservative delay, shortens it while commands
+//! keep succeeding, and backs off whenever one fails with a corrupt or missing reply. The delay
+//! that failed becomes a floor the pacer does not go below, until a long run of successes at the
+//! floor suggests the failure was a fluke.
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacingPolicy {
+    /// The delay between commands to start with.
+    pub initial_delay: Duration,
+    /// The shortest delay ever tried.
+    pub min_delay: Duration,
+    /// The longest delay backing off goes to.
+    pub max_delay: Duration,
+    /// How many commands must succeed in a row before a shorter delay is tried.
+    pub probe_after: u32,
+    /// How many times a command failing with a transient error is sent again.
+    pub retries: u32,
+}
+
+impl PacingPolicy {
+    /// No delay and no retries, for interfaces other than DDC/CI.
+    pub fn none() -> PacingPolicy {
+        PacingPolicy {
+            initial_delay: Duration::ZERO,
+            min_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            probe_after: 0,
+            retries: 0,
+        }
+    }
+}
+
+impl Default for PacingPolicy {
+    /// The policy of DDC/CI monitors.
+    fn default() -> Self {
+        PacingPolicy {
+            initial_delay: Duration::from_millis(200),
+            min_delay: Duration::from_millis(30),
+            max_delay: Duration::from_secs(2),
+            probe_after: 16,
+            retries: 3,
+        }
+    }
+}
+
+/// What a [`Pacer`] has learned so far.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct PacingStats {
+    /// The current delay between commands.
+    pub delay: Duration,
+    /// The shortest delay not known to cause failures.
+    pub floor: Duration,
+    pub commands: u64,
+    /// Attempts that failed with a transient error, including those retried successfully.
+    pub failures: u64,
+    pub retries: u64,
+    /// Commands that still failed after all retries.
+    pub given_up: u64,
+}
+
+/// Whether sending the command again may succeed; see [`ErrorKind::is_transient`].
+pub fn is_transient(e: &Error) -> bool {
+    e.kind().is_transient()
+}
+
+#[derive(Debug)]
+struct PacerState {
+    stats: PacingStats,
+    /// Successes in a row since the delay last changed.
+    streak: u32,
+    next: Option<Instant>,
+}
+
+/// Serializes the commands sent to a monitor and spaces them out; see the [module
+/// documentation](self).
+#[derive(Debug)]
+pub struct Pacer {
+    policy: PacingPolicy,
+    state: Mutex<PacerState>,
+}
+
+impl Pacer {
+    pub fn new(policy: PacingPolicy) -> Pacer {
+        Pacer {
+            state: Mutex::new(PacerState {
+                stats: PacingStats {
+                    delay: policy.initial_delay,
+                    floor: policy.min_delay,
+                    ..Default::default()
+                },
+                streak: 0,
+                next: None,
+            }),
+            policy,
+        }
+    }
+
+    pub fn policy(&self) -> &PacingPolicy {
+        &self.policy
+    }
+
+    pub fn stats(&self) -> PacingStats {
+        self.state.lock().unwrap().stats
+    }
+
+    /// Sends `command` once the delay since the previous command has passed, and sends it again
+    /// while it fails with a transient error and retries are left. Other commands wait until it
+    /// is done.
+    pub fn run<T>(&self, mut command: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut state = self.state.lock().unwrap();
+        state.stats.commands += 1;
+        let mut attempt = 0;
+        loop {
+            if let Some(wait) = state
+                .next
+                .and_then(|next| next.checked_duration_since(Instant::now()))
+            {
+                thread::sleep(wait);
+            }
+            let result = command();
+            let retry = match &result {
+                Ok(_) => {
+                    state.succeeded(&self.policy);
+                    false
+                }
+                Err(e) if is_transient(e) => {
+                    state.failed(&self.policy);
+                    attempt < self.policy.retries
+                }
+                Err(_) => false,
+            };
+            state.next = Some(Instant::now() + state.stats.delay);
+            if !retry {
+                if result.as_ref().is_err_and(is_transient) {
+                    state.stats.given_up += 1;
+                }
+                return result;
+            }
+            attempt += 1;
+            state.stats.retries += 1;
+        }
+    }
+}
+
+impl PacerState {
+    fn succeeded(&mut self, policy: &PacingPolicy) {
+        self.streak += 1;
+        if self.streak < policy.probe_after {
+            return;
+        }
+        self.streak = 0;
+        let stats = &mut self.stats;
+        if stats.delay > stats.floor {
+            stats.delay = (stats.delay * 3 / 4).max(stats.floor);
+        } else {
+            // the failure that raised the floor may have been a fluke
+            stats.floor = (stats.floor * 7 / 8).max(policy.min_delay);
+            stats.delay = stats.floor;
+        }
+    }
+
+    fn failed(&mut self, policy: &PacingPolicy) {
+        self.streak = 0;
+        let stats = &mut self.stats;
+        stats.failures += 1;
+        stats.floor = stats
+            .floor
+            .max(stats.delay + stats.delay / 4)
+            .min(policy.max_delay);
+        stats.delay = (stats.delay * 2).clamp(stats.floor, policy.max_delay);
+    }
+}
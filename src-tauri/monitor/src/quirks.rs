@@ -0,0 +1,325 @@
+//! Fixes for monitors that do not behave as their capabilities string and MCCS say, looked up by
+//! the manufacturer and product code in their EDID.
+//!
+//! The built-in table is `quirks.toml` at the root of the crate. A user file, `MONITOR_QUIRKS` or
+//! else `brightness-tray/quirks.toml` in the configuration directory of the user, is read after it,
+//! and its entries override the fields they set. Each entry looks like:
+//!
+//! ```toml
+//! [[monitor]]
+//! manufacturer = "DEL"
+//! product = 0xA0BA           # every product of the manufacturer if left out
+//! model = "U2415"            # only a comment
+//! disabled = ["0x13"]        # claimed but broken features
+//! maximum = { volume = 100 } # maximums the monitor reports wrong, e.g. 0
+//! remap = { powerstate = { "4" = 5 } } # values to write instead of those requested
+//! power_off = 5              # the power state that turns the monitor off
+//! pacing = { initial_delay_ms = 300, min_delay_ms = 100 }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::edid::Edid;
+use crate::pacing::PacingPolicy;
+use crate::{Error, ErrorKind, Feature, Monitor, Result};
+
+const BUILTIN: &str = include_str!("../quirks.toml");
+
+/// Overrides of the [`PacingPolicy`] of a monitor.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PacingOverride {
+    pub initial_delay_ms: Option<u64>,
+    pub min_delay_ms: Option<u64>,
+    pub max_delay_ms: Option<u64>,
+    pub retries: Option<u32>,
+}
+
+impl PacingOverride {
+    pub fn apply(&self, mut policy: PacingPolicy) -> PacingPolicy {
+        let ms = Duration::from_millis;
+        if let Some(delay) = self.initial_delay_ms {
+            policy.initial_delay = ms(delay);
+        }
+        if let Some(delay) = self.min_delay_ms {
+            policy.min_delay = ms(delay);
+        }
+        if let Some(delay) = self.max_delay_ms {
+            policy.max_delay = ms(delay);
+        }
+        if let Some(retries) = self.retries {
+            policy.retries = retries;
+        }
+        policy
+    }
+
+    fn merge(&mut self, other: &PacingOverride) {
+        self.initial_delay_ms = other.initial_delay_ms.or(self.initial_delay_ms);
+        self.min_delay_ms = other.min_delay_ms.or(self.min_delay_ms);
+        self.max_delay_ms = other.max_delay_ms.or(self.max_delay_ms);
+        self.retries = other.retries.or(self.retries);
+    }
+}
+
+/// The quirks of one monitor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Quirks {
+    /// The VCP codes that fail with [`ErrorKind::NotSupported`] and are left out of the
+    /// capabilities.
+    pub disabled: BTreeSet<u8>,
+    /// The maximums to report in place of those the monitor replies.
+    pub maximum: BTreeMap<u8, u32>,
+    /// For each VCP code, the values to write in place of those requested. Replies are mapped
+    /// back.
+    pub remap: BTreeMap<u8, BTreeMap<u32, u32>>,
+    /// The value of [`Feature::PowerState`] that turns the monitor off.
+    pub power_off: Option<u32>,
+    pub pacing: PacingOverride,
+}
+
+impl Quirks {
+    pub fn is_empty(&self) -> bool {
+        *self == Quirks::default()
+    }
+
+    /// The value to write for `value` of the feature `code`.
+    pub(crate) fn write_value(&self, code: u8, value: u32) -> u32 {
+        self.remap
+            .get(&code)
+            .and_then(|remap| remap.get(&value))
+            .copied()
+            .unwrap_or(value)
+    }
+
+    /// The value to report for `value` read from the feature `code`.
+    pub(crate) fn read_value(&self, code: u8, value: u32) -> u32 {
+        self.remap
+            .get(&code)
+            .and_then(|remap| remap.iter().find(|(_, to)| **to == value))
+            .map_or(value, |(from, _)| *from)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    #[serde(default, rename = "monitor")]
+    monitors: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    manufacturer: String,
+    product: Option<u16>,
+    #[allow(dead_code)]
+    model: Option<String>,
+    disabled: Option<Vec<String>>,
+    maximum: Option<BTreeMap<String, u32>>,
+    remap: Option<BTreeMap<String, BTreeMap<String, u32>>>,
+    power_off: Option<u32>,
+    #[serde(default)]
+    pacing: PacingOverride,
+}
+
+/// An entry with its features and values parsed.
+#[derive(Debug, Clone)]
+struct Rule {
+    manufacturer: String,
+    product: Option<u16>,
+    disabled: Option<BTreeSet<u8>>,
+    maximum: Option<BTreeMap<u8, u32>>,
+    remap: Option<BTreeMap<u8, BTreeMap<u32, u32>>>,
+    power_off: Option<u32>,
+    pacing: PacingOverride,
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+fn code(name: &str) -> Result<u8> {
+    name.parse::<Feature>()
+        .map(|feature| feature.vcp_code())
+        .map_err(|e| invalid(e.to_string()))
+}
+
+/// Parses a decimal value or a hex value such as `0x0F`.
+fn value(s: &str) -> Result<u32> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|_| invalid(format!("invalid value {s}")))
+}
+
+impl Entry {
+    fn parse(self) -> Result<Rule> {
+        let disabled = self
+            .disabled
+            .map(|names| names.iter().map(|name| code(name)).collect::<Result<_>>())
+            .transpose()?;
+        let maximum = self
+            .maximum
+            .map(|maximum| {
+                maximum
+                    .iter()
+                    .map(|(name, maximum)| Ok((code(name)?, *maximum)))
+                    .collect::<Result<_>>()
+            })
+            .transpose()?;
+        let remap = self
+            .remap
+            .map(|remap| {
+                remap
+                    .iter()
+                    .map(|(name, values)| {
+                        let values = values
+                            .iter()
+                            .map(|(from, to)| Ok((value(from)?, *to)))
+                            .collect::<Result<_>>()?;
+                        Ok((code(name)?, values))
+                    })
+                    .collect::<Result<_>>()
+            })
+            .transpose()?;
+        Ok(Rule {
+            manufacturer: self.manufacturer,
+            product: self.product,
+            disabled,
+            maximum,
+            remap,
+            power_off: self.power_off,
+            pacing: self.pacing,
+        })
+    }
+}
+
+/// A table of quirks; later entries override the fields set by earlier ones.
+#[derive(Debug, Clone, Default)]
+pub struct QuirksDb {
+    rules: Vec<Rule>,
+}
+
+impl QuirksDb {
+    pub fn parse(table: &str) -> Result<QuirksDb> {
+        let file: File = toml::from_str(table).map_err(|e| invalid(e.to_string()))?;
+        let rules = file
+            .monitors
+            .into_iter()
+            .map(Entry::parse)
+            .collect::<Result<_>>()?;
+        Ok(QuirksDb { rules })
+    }
+
+    pub fn builtin() -> QuirksDb {
+        QuirksDb::parse(BUILTIN).expect("invalid built-in quirks")
+    }
+
+    /// Appends the entries of `other`, which override those of `self`.
+    pub fn extend(mut self, other: QuirksDb) -> QuirksDb {
+        self.rules.extend(other.rules);
+        self
+    }
+
+    /// Reads the built-in table and the user file, if any.
+    pub fn load() -> Result<QuirksDb> {
+        let db = QuirksDb::builtin();
+        let Some(path) = user_file() else {
+            return Ok(db);
+        };
+        match fs::read_to_string(&path) {
+            Ok(table) => Ok(db.extend(
+                QuirksDb::parse(&table)
+                    .map_err(|e| invalid(format!("invalid quirks {}: {e}", path.display())))?,
+            )),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(db),
+            Err(e) => {
+                let message = format!("failed to read quirks {}: {e}", path.display());
+                Err(io::Error::new(e.kind(), message).into())
+            }
+        }
+    }
+
+    pub fn lookup(&self, edid: &Edid) -> Quirks {
+        let mut quirks = Quirks::default();
+        let matching = self.rules.iter().filter(|rule| {
+            rule.manufacturer.eq_ignore_ascii_case(&edid.manufacturer)
+                && rule
+                    .product
+                    .is_none_or(|product| product == edid.product_code)
+        });
+        for rule in matching {
+            if let Some(disabled) = &rule.disabled {
+                quirks.disabled = disabled.clone();
+            }
+            if let Some(maximum) = &rule.maximum {
+                quirks.maximum = maximum.clone();
+            }
+            if let Some(remap) = &rule.remap {
+                quirks.remap = remap.clone();
+            }
+            quirks.power_off = rule.power_off.or(quirks.power_off);
+            quirks.pacing.merge(&rule.pacing);
+        }
+        quirks
+    }
+}
+
+/// The user file of quirks: `MONITOR_QUIRKS`, or else `brightness-tray/quirks.toml` in
+/// `%APPDATA%`, `$XDG_CONFIG_HOME` or `~/.config`.
+pub fn user_file() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("MONITOR_QUIRKS") {
+        return Some(path.into());
+    }
+    let config = if cfg!(windows) {
+        PathBuf::from(env::var_os("APPDATA")?)
+    } else {
+        match env::var_os("XDG_CONFIG_HOME") {
+            Some(config) => config.into(),
+            None => Path::new(&env::var_os("HOME")?).join(".config"),
+        }
+    };
+    Some(config.join("brightness-tray").join("quirks.toml"))
+}
+
+/// The built-in table and the user file, read once; [`crate::enumerate`] applies it.
+///
+/// A user file that cannot be read or parsed is left out; [`user_file_error`] tells why.
+pub fn database() -> &'static QuirksDb {
+    &loaded().0
+}
+
+/// Why the user file was left out of [`database`], for the caller to report.
+pub fn user_file_error() -> Option<&'static Error> {
+    loaded().1.as_ref()
+}
+
+fn loaded() -> &'static (QuirksDb, Option<Error>) {
+    static DATABASE: OnceLock<(QuirksDb, Option<Error>)> = OnceLock::new();
+    DATABASE.get_or_init(|| match QuirksDb::load() {
+        Ok(db) => (db, None),
+        Err(e) => (QuirksDb::builtin(), Some(e)),
+    })
+}
+
+/// Looks up the quirks of `monitors` by their EDIDs, already read, and applies them.
+pub(crate) fn apply(monitors: &mut [Monitor], edids: &[Option<Edid>], db: &QuirksDb) {
+    for (monitor, edid) in monitors.iter_mut().zip(edids) {
+        let Some(edid) = edid else {
+            continue;
+        };
+        let quirks = db.lookup(edid);
+        if !quirks.is_empty() {
+            monitor.set_quirks(quirks);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Virtual monitors described in a TOML file, for developing and demoing without DDC/CI hardware.
+//!
+//! ```toml
+//! [[monitor]]
+//! id = "SIM#DELL#0"
+//! name = "DELL U2720Q"
+//! connector = "DisplayPort"
+//! capabilities = "(prot(monitor)type(LCD)vcp(10 12 60(0F 11)))"
+//! edid = { manufacturer = "DEL", product_code = 0xA0F0, serial_number = 1 }
+//! latency_ms = 40
+//! failure_rate = 0.05
+//! ignores_writes = false
+//!
+//! [monitor.features]
+//! luminance = 50
+//! contrast = { current = 75, maximum = 100 }
+//! "0x60" = { current = 0x0F, maximum = 0x11 }
+//!
+//! [monitor.clamp]
+//! luminance = [10, 90]
+//! ```
+//!
+//! The EDID may also be given as hex. The monitors are paced like real DDC/CI monitors unless
+//! `paced = false`, and monitors given the same `bus`, e.g. `bus = "dock"`, take turns like the
+//! monitors of a daisy chain. With `reports_changes = true`, a monitor implements New Control
+//! Value and Active Control; see [`crate::changes`].
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::edid::Edid;
+use crate::mock::{MockBackend, MockMonitor};
+use crate::pacing::PacingPolicy;
+use crate::trace::decode_hex;
+use crate::{ConnectorType, Error, ErrorKind, Feature, Interface, Reply, Result};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Simulation {
+    #[serde(default, rename = "monitor")]
+    monitors: Vec<MonitorConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MonitorConfig {
+    id: String,
+    name: Option<String>,
+    connector: Option<ConnectorType>,
+    capabilities: Option<String>,
+    edid: Option<EdidConfig>,
+    #[serde(default)]
+    features: BTreeMap<String, FeatureConfig>,
+    #[serde(default)]
+    latency_ms: u64,
+    #[serde(default)]
+    failure_rate: f64,
+    /// Seeds the random failures; by default a hash of the id.
+    seed: Option<u64>,
+    #[serde(default)]
+    ignores_writes: bool,
+    #[serde(default)]
+    clamp: BTreeMap<String, (u32, u32)>,
+    #[serde(default = "paced")]
+    paced: bool,
+    bus: Option<String>,
+    #[serde(default)]
+    reports_changes: bool,
+}
+
+fn paced() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EdidConfig {
+    Hex(String),
+    Fields {
+        manufacturer: String,
+        product_code: u16,
+        #[serde(default)]
+        serial_number: u32,
+        name: Option<String>,
+        #[serde(default = "year")]
+        year: u16,
+    },
+}
+
+fn year() -> u16 {
+    2024
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FeatureConfig {
+    Current(u32),
+    Reply {
+        current: u32,
+        #[serde(default = "maximum")]
+        maximum: u32,
+        #[serde(default = "source")]
+        source: Interface,
+    },
+}
+
+fn maximum() -> u32 {
+    100
+}
+
+fn source() -> Interface {
+    Interface::DDCCI
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+fn feature(id: &str, name: &str) -> Result<Feature> {
+    name.parse()
+        .map_err(|e| invalid(format!("monitor {id}: {e}")))
+}
+
+impl MonitorConfig {
+    fn build(self) -> Result<MockMonitor> {
+        let id = self.id;
+        let mut monitor = MockMonitor::new(&id);
+        if let Some(name) = self.name {
+            monitor = monitor.name(name);
+        }
+        if let Some(connector) = self.connector {
+            monitor = monitor.connector(connector);
+        }
+        if let Some(capabilities) = self.capabilities {
+            monitor = monitor.capabilities(capabilities);
+        }
+        match self.edid {
+            Some(EdidConfig::Hex(hex)) => {
+                let edid = decode_hex(&hex)
+                    .ok_or_else(|| invalid(format!("monitor {id}: invalid EDID")))?;
+                monitor = monitor.edid(edid);
+            }
+            Some(EdidConfig::Fields {
+                manufacturer,
+                product_code,
+                serial_number,
+                name,
+                year,
+            }) => {
+                let edid = Edid {
+                    manufacturer,
+                    product_code,
+                    serial_number,
+                    year,
+                    version: (1, 4),
+                    name,
+                    ..Default::default()
+                };
+                monitor = monitor.edid(edid.encode());
+            }
+            None => {}
+        }
+        for (name, config) in &self.features {
+            let reply = match *config {
+                FeatureConfig::Current(current) => Reply {
+                    current,
+                    maximum: maximum(),
+                    source: source(),
+                },
+                FeatureConfig::Reply {
+                    current,
+                    maximum,
+                    source,
+                } => Reply {
+                    current,
+                    maximum,
+                    source,
+                },
+            };
+            monitor = monitor.feature(feature(&id, name)?, reply);
+        }
+        for (name, &(min, max)) in &self.clamp {
+            if min > max {
+                return Err(invalid(format!("monitor {id}: empty clamp of {name}")));
+            }
+            monitor = monitor.clamp(feature(&id, name)?, min, max);
+        }
+        if !(0.0..=1.0).contains(&self.failure_rate) {
+            return Err(invalid(format!("monitor {id}: failure rate out of 0..=1")));
+        }
+        let seed = self.seed.unwrap_or_else(|| crate::watch::hash(&id));
+        monitor = monitor
+            .latency(Duration::from_millis(self.latency_ms))
+            .failure_rate(self.failure_rate, seed);
+        if self.ignores_writes {
+            monitor = monitor.ignore_writes();
+        }
+        if self.paced {
+            monitor = monitor.pacing(PacingPolicy::default());
+        }
+        if self.reports_changes {
+            monitor = monitor.report_changes();
+        }
+        if let Some(bus) = self.bus {
+            monitor = monitor.bus(bus);
+        }
+        Ok(monitor)
+    }
+}
+
+/// Parses a simulation into a backend of mock monitors.
+pub fn parse(simulation: &str) -> Result<MockBackend> {
+    let simulation: Simulation = toml::from_str(simulation).map_err(|e| invalid(e.to_string()))?;
+    simulation
+        .monitors
+        .into_iter()
+        .try_fold(MockBackend::new(), |backend, config| {
+            Ok(backend.with_monitor(config.build()?))
+        })
+}
+
+/// Reads a simulation from a file, whose path the errors name.
+pub fn load(path: impl AsRef<Path>) -> Result<MockBackend> {
+    let path = path.as_ref();
+    let simulation = fs::read_to_string(path).map_err(|e| {
+        let message = format!("failed to read simulation {}: {e}", path.display());
+        Error::from(io::Error::new(e.kind(), message))
+    })?;
+    parse(&simulation).map_err(|e| {
+        let message = format!("invalid simulation {}: {e}", path.display());
+        Error::new(e.kind(), message)
+    })
+}
@@ -0,0 +1,445 @@
+//! Recording the commands sent to monitors, and serving recordings back.
+//!
+//! [`TraceBackend`] wraps another backend and writes a [`Record`] of every command its monitors
+//! answer as a line of JSON, e.g. with `MONITOR_TRACE=trace.jsonl`. [`ReplayBackend`] serves such a
+//! file back, e.g. with `MONITOR_REPLAY=trace.jsonl`, so that the trace attached to a bug report
+//! reproduces it without the monitor.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::pacing::PacingPolicy;
+use crate::{
+    ConnectorType, Error, ErrorKind, Feature, Monitor, MonitorBackend, MonitorDevice, Reply, Result,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Op {
+    /// The monitor was listed by the backend.
+    Enumerate,
+    GetFeature,
+    SetFeature,
+    GetName,
+    GetCapabilities,
+    GetEdid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceError {
+    pub kind: ErrorKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl From<&Error> for TraceError {
+    fn from(e: &Error) -> Self {
+        TraceError {
+            kind: e.kind(),
+            message: e.get_ref().map(ToString::to_string),
+        }
+    }
+}
+
+impl From<TraceError> for Error {
+    fn from(e: TraceError) -> Self {
+        match e.message {
+            Some(message) => Error::new(e.kind, message),
+            None => Error::from(e.kind),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// When the command was sent, in milliseconds since the Unix epoch.
+    pub time: u64,
+    /// The OS path of the monitor.
+    pub monitor: String,
+    pub op: Op,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connector: Option<ConnectorType>,
+    /// The VCP code of the feature read or written.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<u8>,
+    /// The value written.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply: Option<Reply>,
+    /// The name or capabilities string returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// The EDID returned, in hex.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edid: Option<String>,
+    /// How long the command took, in microseconds.
+    pub latency_us: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<TraceError>,
+}
+
+impl Record {
+    fn new(time: SystemTime, monitor: &str, op: Op) -> Record {
+        Record {
+            time: time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |time| time.as_millis() as u64),
+            monitor: monitor.to_owned(),
+            op,
+            connector: None,
+            code: None,
+            value: None,
+            reply: None,
+            text: None,
+            edid: None,
+            latency_us: 0,
+            error: None,
+        }
+    }
+}
+
+fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let pairs = hex.as_bytes().chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+    pairs
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+struct Sink(Mutex<Box<dyn Write + Send>>);
+
+impl fmt::Debug for Sink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sink")
+    }
+}
+
+impl Sink {
+    /// Writes and flushes a line, so that the trace is complete even if the app crashes. Failures
+    /// are ignored; tracing must not break controlling the monitors.
+    fn write(&self, record: &Record) {
+        let mut writer = self.0.lock().unwrap();
+        if let Ok(line) = serde_json::to_string(record) {
+            let _ = writeln!(writer, "{line}").and_then(|_| writer.flush());
+        }
+    }
+}
+
+/// Opens `path` to append records to it, creating it if needed.
+pub fn append(path: impl AsRef<Path>) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Records the commands sent to the monitors of another backend.
+#[derive(Debug)]
+pub struct TraceBackend {
+    inner: Box<dyn MonitorBackend>,
+    sink: Arc<Sink>,
+}
+
+impl TraceBackend {
+    pub fn new(
+        inner: Box<dyn MonitorBackend>,
+        writer: impl Write + Send + 'static,
+    ) -> TraceBackend {
+        TraceBackend {
+            inner,
+            sink: Arc::new(Sink(Mutex::new(Box::new(writer)))),
+        }
+    }
+}
+
+impl MonitorBackend for TraceBackend {
+    fn enumerate(&self) -> Vec<Monitor> {
+        let time = SystemTime::now();
+        self.inner
+            .enumerate()
+            .into_iter()
+            .map(|monitor| {
+                let Monitor {
+                    id,
+                    identity,
+                    connector,
+                    device,
+                    pacer,
+                    quirks,
+                } = monitor;
+                let name = id.to_string_lossy().into_owned();
+                let mut record = Record::new(time, &name, Op::Enumerate);
+                record.connector = connector;
+                self.sink.write(&record);
+                // below the pacer, so that every retry is recorded
+                let device = Box::new(TraceDevice {
+                    monitor: name,
+                    inner: device,
+                    sink: self.sink.clone(),
+                });
+                Monitor {
+                    id,
+                    identity,
+                    connector,
+                    device,
+                    pacer,
+                    quirks,
+                }
+            })
+            .collect()
+    }
+
+    fn generation(&self) -> Option<u64> {
+        self.inner.generation()
+    }
+}
+
+#[derive(Debug)]
+struct TraceDevice {
+    monitor: String,
+    inner: Box<dyn MonitorDevice>,
+    sink: Arc<Sink>,
+}
+
+impl TraceDevice {
+    fn trace<T>(
+        &self,
+        op: Op,
+        feature: Option<Feature>,
+        value: Option<u32>,
+        command: impl FnOnce(&dyn MonitorDevice) -> Result<T>,
+        fill: impl FnOnce(&mut Record, &T),
+    ) -> Result<T> {
+        let time = SystemTime::now();
+        let start = Instant::now();
+        let result = command(&*self.inner);
+        let mut record = Record::new(time, &self.monitor, op);
+        record.latency_us = start.elapsed().as_micros() as u64;
+        record.code = feature.map(|feature| feature.vcp_code());
+        record.value = value;
+        match &result {
+            Ok(value) => fill(&mut record, value),
+            Err(e) => record.error = Some(TraceError::from(e)),
+        }
+        self.sink.write(&record);
+        result
+    }
+}
+
+impl MonitorDevice for TraceDevice {
+    fn get_feature(&self, feature: Feature) -> Result<Reply> {
+        self.trace(
+            Op::GetFeature,
+            Some(feature),
+            None,
+            |device| device.get_feature(feature),
+            |record, reply| record.reply = Some(*reply),
+        )
+    }
+
+    fn set_feature(&self, feature: Feature, value: u32) -> Result<()> {
+        self.trace(
+            Op::SetFeature,
+            Some(feature),
+            Some(value),
+            |device| device.set_feature(feature, value),
+            |_, _| {},
+        )
+    }
+
+    fn get_user_friendly_name(&self) -> Result<Option<OsString>> {
+        self.trace(
+            Op::GetName,
+            None,
+            None,
+            |device| device.get_user_friendly_name(),
+            |record, name| record.text = name.as_ref().map(|name| name.to_string_lossy().into()),
+        )
+    }
+
+    fn get_capabilities_string(&self) -> Result<String> {
+        self.trace(
+            Op::GetCapabilities,
+            None,
+            None,
+            |device| device.get_capabilities_string(),
+            |record, caps| record.text = Some(caps.clone()),
+        )
+    }
+
+    fn get_edid(&self) -> Result<Vec<u8>> {
+        self.trace(
+            Op::GetEdid,
+            None,
+            None,
+            |device| device.get_edid(),
+            |record, edid| record.edid = Some(encode_hex(edid)),
+        )
+    }
+
+    fn pacing(&self) -> PacingPolicy {
+        self.inner.pacing()
+    }
+
+    fn bus(&self) -> Option<String> {
+        self.inner.bus()
+    }
+}
+
+type Replies = BTreeMap<(Op, Option<u8>), VecDeque<Record>>;
+
+/// Serves the monitors of a trace.
+///
+/// Each command gets the replies recorded for it in order, and the last one once they run out;
+/// commands the trace never saw fail with [`ErrorKind::NotSupported`]. Writes are not applied, so
+/// reads return what the monitor answered when the trace was recorded. The replies are shared
+/// between enumerations, like the monitors they stand for.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayBackend {
+    monitors: Vec<(String, Option<ConnectorType>, Arc<Mutex<Replies>>)>,
+}
+
+impl ReplayBackend {
+    pub fn new(records: impl IntoIterator<Item = Record>) -> ReplayBackend {
+        let mut backend = ReplayBackend::default();
+        for record in records {
+            let index = match backend
+                .monitors
+                .iter()
+                .position(|(id, ..)| *id == record.monitor)
+            {
+                Some(index) => index,
+                None => {
+                    backend
+                        .monitors
+                        .push((record.monitor.clone(), None, Default::default()));
+                    backend.monitors.len() - 1
+                }
+            };
+            let (_, connector, replies) = &mut backend.monitors[index];
+            if record.op == Op::Enumerate {
+                *connector = record.connector;
+                continue;
+            }
+            replies
+                .lock()
+                .unwrap()
+                .entry((record.op, record.code))
+                .or_default()
+                .push_back(record);
+        }
+        backend
+    }
+
+    /// Reads a trace of one record per line; blank lines are skipped.
+    pub fn read(reader: impl BufRead) -> io::Result<ReplayBackend> {
+        let mut records = Vec::new();
+        for (n, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {e}", n + 1))
+            })?;
+            records.push(record);
+        }
+        Ok(ReplayBackend::new(records))
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<ReplayBackend> {
+        ReplayBackend::read(BufReader::new(File::open(path)?))
+    }
+}
+
+impl MonitorBackend for ReplayBackend {
+    fn enumerate(&self) -> Vec<Monitor> {
+        self.monitors
+            .iter()
+            .map(|(id, connector, replies)| {
+                let device = Box::new(ReplayDevice {
+                    replies: replies.clone(),
+                });
+                let monitor = Monitor::new(id.into(), device);
+                match connector {
+                    Some(connector) => monitor.with_connector(*connector),
+                    None => monitor,
+                }
+            })
+            .collect()
+    }
+
+    fn generation(&self) -> Option<u64> {
+        Some(0)
+    }
+}
+
+#[derive(Debug)]
+struct ReplayDevice {
+    replies: Arc<Mutex<Replies>>,
+}
+
+impl ReplayDevice {
+    fn next(&self, op: Op, feature: Option<Feature>) -> Result<Record> {
+        let mut replies = self.replies.lock().unwrap();
+        let queue = replies
+            .get_mut(&(op, feature.map(|feature| feature.vcp_code())))
+            .filter(|queue| !queue.is_empty())
+            .ok_or_else(|| Error::from(ErrorKind::NotSupported))?;
+        let record = if queue.len() > 1 {
+            queue.pop_front().unwrap()
+        } else {
+            queue[0].clone()
+        };
+        match record.error {
+            Some(e) => Err(e.into()),
+            None => Ok(record),
+        }
+    }
+}
+
+fn incomplete(what: &str) -> Error {
+    Error::new(ErrorKind::Io, format!("the trace has no {what}"))
+}
+
+impl MonitorDevice for ReplayDevice {
+    fn get_feature(&self, feature: Feature) -> Result<Reply> {
+        self.next(Op::GetFeature, Some(feature))?
+            .reply
+            .ok_or_else(|| incomplete("reply"))
+    }
+
+    fn set_feature(&self, feature: Feature, _value: u32) -> Result<()> {
+        self.next(Op::SetFeature, Some(feature)).map(drop)
+    }
+
+    fn get_user_friendly_name(&self) -> Result<Option<OsString>> {
+        Ok(self.next(Op::GetName, None)?.text.map(OsString::from))
+    }
+
+    fn get_capabilities_string(&self) -> Result<String> {
+        self.next(Op::GetCapabilities, None)?
+            .text
+            .ok_or_else(|| incomplete("capabilities string"))
+    }
+
+    fn get_edid(&self) -> Result<Vec<u8>> {
+        self.next(Op::GetEdid, None)?
+            .edid
+            .as_deref()
+            .and_then(decode_hex)
+            .ok_or_else(|| incomplete("EDID"))
+    }
+}
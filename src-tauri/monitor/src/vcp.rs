@@ -0,0 +1,794 @@
+//! A description of the standard VCP codes of MCCS 2.2a and 3.0.
+
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Group {
+    Preset,
+    Image,
+    Display,
+    Geometry,
+    Miscellaneous,
+    Audio,
+    Dpvl,
+    Manufacturer,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// A value in `0..=maximum`.
+    Continuous,
+    /// One of an enumerated set of values.
+    NonContinuous,
+    /// A byte string, read and written with the table commands.
+    Table,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+impl Access {
+    pub fn readable(&self) -> bool {
+        *self != Access::WriteOnly
+    }
+
+    pub fn writable(&self) -> bool {
+        *self != Access::ReadOnly
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VcpCode {
+    pub code: u8,
+    pub name: &'static str,
+    pub group: Group,
+    pub kind: Kind,
+    pub access: Access,
+    /// The names of the values of a non-continuous code.
+    pub values: &'static [(u8, &'static str)],
+}
+
+impl VcpCode {
+    /// Names `value`, of which only the low byte is compared: many monitors set the high byte of
+    /// non-continuous values, e.g. of the input source.
+    pub fn value_name(&self, value: u32) -> Option<&'static str> {
+        self.values
+            .iter()
+            .find(|(v, _)| u32::from(*v) == value & 0xFF)
+            .map(|(_, name)| *name)
+    }
+}
+
+impl fmt::Display for VcpCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#04x} {}", self.code, self.name)
+    }
+}
+
+const fn code(code: u8, name: &'static str, group: Group, kind: Kind, access: Access) -> VcpCode {
+    VcpCode {
+        code,
+        name,
+        group,
+        kind,
+        access,
+        values: &[],
+    }
+}
+
+const fn nc(
+    code: u8,
+    name: &'static str,
+    group: Group,
+    access: Access,
+    values: &'static [(u8, &'static str)],
+) -> VcpCode {
+    VcpCode {
+        code,
+        name,
+        group,
+        kind: Kind::NonContinuous,
+        access,
+        values,
+    }
+}
+
+use Access::*;
+use Group::*;
+use Kind::*;
+
+const NEW_CONTROL_VALUES: &[(u8, &str)] = &[
+    (0x01, "No new control values"),
+    (0x02, "New control values"),
+    (0xFF, "No user controls"),
+];
+
+const COLOR_PRESETS: &[(u8, &str)] = &[
+    (0x01, "sRGB"),
+    (0x02, "Native"),
+    (0x03, "4000K"),
+    (0x04, "5000K"),
+    (0x05, "6500K"),
+    (0x06, "7500K"),
+    (0x07, "8200K"),
+    (0x08, "9300K"),
+    (0x09, "10000K"),
+    (0x0A, "11500K"),
+    (0x0B, "User 1"),
+    (0x0C, "User 2"),
+    (0x0D, "User 3"),
+];
+
+const AUTO_SETUP: &[(u8, &str)] = &[(0x00, "Not supported"), (0x01, "Off"), (0x02, "On")];
+
+const SETTINGS: &[(u8, &str)] = &[
+    (0x01, "Store current settings"),
+    (0x02, "Restore factory defaults for current mode"),
+];
+
+pub const INPUT_SOURCES: &[(u8, &str)] = &[
+    (0x01, "VGA-1"),
+    (0x02, "VGA-2"),
+    (0x03, "DVI-1"),
+    (0x04, "DVI-2"),
+    (0x05, "Composite-1"),
+    (0x06, "Composite-2"),
+    (0x07, "S-Video-1"),
+    (0x08, "S-Video-2"),
+    (0x09, "Tuner-1"),
+    (0x0A, "Tuner-2"),
+    (0x0B, "Tuner-3"),
+    (0x0C, "Component-1"),
+    (0x0D, "Component-2"),
+    (0x0E, "Component-3"),
+    (0x0F, "DisplayPort-1"),
+    (0x10, "DisplayPort-2"),
+    (0x11, "HDMI-1"),
+    (0x12, "HDMI-2"),
+    (0x13, "USB-C-1"),
+    (0x14, "USB-C-2"),
+];
+
+const SPEAKERS: &[(u8, &str)] = &[
+    (0x00, "Front L/R"),
+    (0x01, "Side L/R"),
+    (0x02, "Rear L/R"),
+    (0x03, "Center/Subwoofer"),
+];
+
+const AMBIENT_LIGHT_SENSOR: &[(u8, &str)] = &[(0x01, "Disabled"), (0x02, "Enabled")];
+
+const MIRROR: &[(u8, &str)] = &[(0x01, "Normal"), (0x02, "Mirrored")];
+
+const DISPLAY_SCALING: &[(u8, &str)] = &[
+    (0x01, "No scaling"),
+    (0x02, "Max image, no aspect ratio distortion"),
+    (0x03, "Max vertical image, no aspect ratio distortion"),
+    (0x04, "Max horizontal image, no aspect ratio distortion"),
+    (0x05, "Max vertical image with aspect ratio distortion"),
+    (0x06, "Max horizontal image with aspect ratio distortion"),
+    (0x07, "Linear expansion"),
+    (0x08, "Non-linear expansion"),
+];
+
+const MUTE: &[(u8, &str)] = &[(0x01, "Muted"), (0x02, "Unmuted")];
+
+const AUDIO_PROCESSOR_MODE: &[(u8, &str)] = &[
+    (0x00, "Speaker off"),
+    (0x01, "Mono"),
+    (0x02, "Stereo"),
+    (0x03, "Stereo expanded"),
+    (0x11, "SRS 2.0"),
+    (0x12, "SRS 2.1"),
+    (0x13, "SRS 3.1"),
+    (0x14, "SRS 4.1"),
+    (0x15, "SRS 5.1"),
+    (0x16, "SRS 6.1"),
+    (0x17, "SRS 7.1"),
+    (0x21, "Dolby 2.0"),
+    (0x22, "Dolby 2.1"),
+    (0x23, "Dolby 3.1"),
+    (0x24, "Dolby 4.1"),
+    (0x25, "Dolby 5.1"),
+    (0x26, "Dolby 6.1"),
+    (0x27, "Dolby 7.1"),
+    (0x31, "THX 2.0"),
+    (0x32, "THX 2.1"),
+    (0x33, "THX 3.1"),
+    (0x34, "THX 4.1"),
+    (0x35, "THX 5.1"),
+    (0x36, "THX 6.1"),
+    (0x37, "THX 7.1"),
+];
+
+const ON_OFF: &[(u8, &str)] = &[(0x01, "Off"), (0x02, "On")];
+
+const SCREEN_ORIENTATION: &[(u8, &str)] = &[
+    (0x01, "0 degrees"),
+    (0x02, "90 degrees"),
+    (0x03, "180 degrees"),
+    (0x04, "270 degrees"),
+    (0xFF, "Not applicable"),
+];
+
+const SUBPIXEL_LAYOUT: &[(u8, &str)] = &[
+    (0x00, "Undefined"),
+    (0x01, "RGB vertical stripe"),
+    (0x02, "RGB horizontal stripe"),
+    (0x03, "BGR vertical stripe"),
+    (0x04, "BGR horizontal stripe"),
+    (0x05, "Quad pixel, red top left"),
+    (0x06, "Quad pixel, red bottom left"),
+    (0x07, "Delta (triad)"),
+    (0x08, "Mosaic"),
+];
+
+const DISPLAY_TECHNOLOGY: &[(u8, &str)] = &[
+    (0x01, "CRT (shadow mask)"),
+    (0x02, "CRT (aperture grill)"),
+    (0x03, "LCD (active matrix)"),
+    (0x04, "LCoS"),
+    (0x05, "Plasma"),
+    (0x06, "OLED"),
+    (0x07, "EL"),
+    (0x08, "Dynamic MEM"),
+    (0x09, "Static MEM"),
+];
+
+const MONITOR_STATUS: &[(u8, &str)] = &[
+    (0x00, "Raster scan, no stereo"),
+    (0x01, "Raster scan, field sequential stereo"),
+];
+
+const LINK_CONTROL: &[(u8, &str)] = &[
+    (0x00, "Link shutdown disabled"),
+    (0x01, "Link shutdown enabled"),
+];
+
+const OSD: &[(u8, &str)] = &[
+    (0x00, "Ignored"),
+    (0x01, "OSD disabled"),
+    (0x02, "OSD enabled"),
+    (0xFF, "Not supported"),
+];
+
+const OSD_LANGUAGES: &[(u8, &str)] = &[
+    (0x00, "Ignored"),
+    (0x01, "Chinese (traditional)"),
+    (0x02, "English"),
+    (0x03, "French"),
+    (0x04, "German"),
+    (0x05, "Italian"),
+    (0x06, "Japanese"),
+    (0x07, "Korean"),
+    (0x08, "Portuguese (Portugal)"),
+    (0x09, "Russian"),
+    (0x0A, "Spanish"),
+    (0x0B, "Swedish"),
+    (0x0C, "Turkish"),
+    (0x0D, "Chinese (simplified)"),
+    (0x0E, "Portuguese (Brazil)"),
+    (0x0F, "Arabic"),
+    (0x10, "Bulgarian"),
+    (0x11, "Croatian"),
+    (0x12, "Czech"),
+    (0x13, "Danish"),
+    (0x14, "Dutch"),
+    (0x15, "Estonian"),
+    (0x16, "Finnish"),
+    (0x17, "Greek"),
+    (0x18, "Hebrew"),
+    (0x19, "Hindi"),
+    (0x1A, "Hungarian"),
+    (0x1B, "Latvian"),
+    (0x1C, "Lithuanian"),
+    (0x1D, "Norwegian"),
+    (0x1E, "Polish"),
+    (0x1F, "Romanian"),
+    (0x20, "Serbian"),
+    (0x21, "Slovak"),
+    (0x22, "Slovenian"),
+    (0x23, "Thai"),
+    (0x24, "Ukrainian"),
+    (0x25, "Vietnamese"),
+];
+
+const STEREO_VIDEO_MODE: &[(u8, &str)] = &[(0x00, "Mono"), (0x01, "Stereo")];
+
+pub const POWER_MODES: &[(u8, &str)] = &[
+    (0x01, "On"),
+    (0x02, "Standby"),
+    (0x03, "Suspend"),
+    (0x04, "Off"),
+    (0x05, "Off (hard)"),
+];
+
+const AUXILIARY_POWER: &[(u8, &str)] = &[(0x01, "Disable"), (0x02, "Enable")];
+
+const SCAN_MODE: &[(u8, &str)] = &[
+    (0x00, "Normal"),
+    (0x01, "Underscan"),
+    (0x02, "Overscan"),
+    (0x03, "Widescreen"),
+];
+
+const IMAGE_MODE: &[(u8, &str)] = &[
+    (0x00, "No effect"),
+    (0x01, "Full mode"),
+    (0x02, "Zoom mode"),
+    (0x03, "Squeeze mode"),
+    (0x04, "Variable"),
+];
+
+const DISPLAY_APPLICATION: &[(u8, &str)] = &[
+    (0x00, "Standard"),
+    (0x01, "Productivity"),
+    (0x02, "Mixed"),
+    (0x03, "Movie"),
+    (0x04, "User defined"),
+    (0x05, "Games"),
+    (0x06, "Sports"),
+    (0x07, "Professional"),
+    (0x08, "Standard, intermediate power"),
+    (0x09, "Standard, low power"),
+    (0x0A, "Demonstration"),
+    (0xF0, "Dynamic contrast"),
+];
+
+/// The standard codes, sorted by code.
+static VCP_CODES: &[VcpCode] = &[
+    nc(0x01, "Degauss", Miscellaneous, WriteOnly, &[]),
+    nc(
+        0x02,
+        "New Control Value",
+        Miscellaneous,
+        ReadWrite,
+        NEW_CONTROL_VALUES,
+    ),
+    nc(0x03, "Soft Controls", Miscellaneous, ReadWrite, &[]),
+    nc(0x04, "Restore Factory Defaults", Preset, WriteOnly, &[]),
+    nc(
+        0x05,
+        "Restore Factory Luminance/Contrast Defaults",
+        Preset,
+        WriteOnly,
+        &[],
+    ),
+    nc(
+        0x06,
+        "Restore Factory Geometry Defaults",
+        Preset,
+        WriteOnly,
+        &[],
+    ),
+    nc(
+        0x08,
+        "Restore Factory Color Defaults",
+        Preset,
+        WriteOnly,
+        &[],
+    ),
+    nc(0x0A, "Restore Factory TV Defaults", Preset, WriteOnly, &[]),
+    nc(0x0B, "Color Temperature Increment", Image, ReadOnly, &[]),
+    code(
+        0x0C,
+        "Color Temperature Request",
+        Image,
+        Continuous,
+        ReadWrite,
+    ),
+    code(0x0E, "Clock", Image, Continuous, ReadWrite),
+    code(0x10, "Luminance", Image, Continuous, ReadWrite),
+    code(0x11, "Flesh Tone Enhancement", Image, Continuous, ReadWrite),
+    code(0x12, "Contrast", Image, Continuous, ReadWrite),
+    code(0x13, "Backlight Control", Image, Continuous, ReadWrite),
+    nc(0x14, "Select Color Preset", Image, ReadWrite, COLOR_PRESETS),
+    code(0x16, "Video Gain: Red", Image, Continuous, ReadWrite),
+    code(
+        0x17,
+        "User Color Vision Compensation",
+        Image,
+        Continuous,
+        ReadWrite,
+    ),
+    code(0x18, "Video Gain: Green", Image, Continuous, ReadWrite),
+    code(0x1A, "Video Gain: Blue", Image, Continuous, ReadWrite),
+    code(0x1C, "Focus", Image, Continuous, ReadWrite),
+    nc(0x1E, "Auto Setup", Image, ReadWrite, AUTO_SETUP),
+    nc(0x1F, "Auto Color Setup", Image, ReadWrite, AUTO_SETUP),
+    code(0x20, "Horizontal Position", Geometry, Continuous, ReadWrite),
+    code(0x22, "Horizontal Size", Geometry, Continuous, ReadWrite),
+    code(
+        0x24,
+        "Horizontal Pincushion",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x26,
+        "Horizontal Pincushion Balance",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x28,
+        "Horizontal Convergence R/B",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x29,
+        "Horizontal Convergence M/G",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x2A,
+        "Horizontal Linearity",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x2C,
+        "Horizontal Linearity Balance",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    nc(0x2E, "Gray Scale Expansion", Image, ReadWrite, &[]),
+    code(0x30, "Vertical Position", Geometry, Continuous, ReadWrite),
+    code(0x32, "Vertical Size", Geometry, Continuous, ReadWrite),
+    code(0x34, "Vertical Pincushion", Geometry, Continuous, ReadWrite),
+    code(
+        0x36,
+        "Vertical Pincushion Balance",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x38,
+        "Vertical Convergence R/B",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x39,
+        "Vertical Convergence M/G",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(0x3A, "Vertical Linearity", Geometry, Continuous, ReadWrite),
+    code(
+        0x3C,
+        "Vertical Linearity Balance",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(0x3E, "Clock Phase", Image, Continuous, ReadWrite),
+    code(
+        0x40,
+        "Horizontal Parallelogram",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x41,
+        "Vertical Parallelogram",
+        Geometry,
+        Continuous,
+        ReadWrite,
+    ),
+    code(0x42, "Horizontal Keystone", Geometry, Continuous, ReadWrite),
+    code(0x43, "Vertical Keystone", Geometry, Continuous, ReadWrite),
+    code(0x44, "Rotation", Geometry, Continuous, ReadWrite),
+    code(0x46, "Top Corner Flare", Geometry, Continuous, ReadWrite),
+    code(0x48, "Top Corner Hook", Geometry, Continuous, ReadWrite),
+    code(0x4A, "Bottom Corner Flare", Geometry, Continuous, ReadWrite),
+    code(0x4C, "Bottom Corner Hook", Geometry, Continuous, ReadWrite),
+    nc(0x52, "Active Control", Miscellaneous, ReadOnly, &[]),
+    nc(
+        0x54,
+        "Performance Preservation",
+        Miscellaneous,
+        ReadWrite,
+        &[],
+    ),
+    code(0x56, "Horizontal Moire", Image, Continuous, ReadWrite),
+    code(0x58, "Vertical Moire", Image, Continuous, ReadWrite),
+    code(0x59, "6 Axis Saturation: Red", Image, Continuous, ReadWrite),
+    code(
+        0x5A,
+        "6 Axis Saturation: Yellow",
+        Image,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x5B,
+        "6 Axis Saturation: Green",
+        Image,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x5C,
+        "6 Axis Saturation: Cyan",
+        Image,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x5D,
+        "6 Axis Saturation: Blue",
+        Image,
+        Continuous,
+        ReadWrite,
+    ),
+    code(
+        0x5E,
+        "6 Axis Saturation: Magenta",
+        Image,
+        Continuous,
+        ReadWrite,
+    ),
+    nc(
+        0x60,
+        "Input Source",
+        Miscellaneous,
+        ReadWrite,
+        INPUT_SOURCES,
+    ),
+    code(0x62, "Audio Speaker Volume", Audio, Continuous, ReadWrite),
+    nc(0x63, "Speaker Select", Audio, ReadWrite, SPEAKERS),
+    code(
+        0x64,
+        "Audio Microphone Volume",
+        Audio,
+        Continuous,
+        ReadWrite,
+    ),
+    nc(
+        0x66,
+        "Ambient Light Sensor",
+        Miscellaneous,
+        ReadWrite,
+        AMBIENT_LIGHT_SENSOR,
+    ),
+    code(0x6B, "Backlight Level: White", Image, Continuous, ReadWrite),
+    code(0x6C, "Video Black Level: Red", Image, Continuous, ReadWrite),
+    code(0x6D, "Backlight Level: Red", Image, Continuous, ReadWrite),
+    code(
+        0x6E,
+        "Video Black Level: Green",
+        Image,
+        Continuous,
+        ReadWrite,
+    ),
+    code(0x6F, "Backlight Level: Green", Image, Continuous, ReadWrite),
+    code(
+        0x70,
+        "Video Black Level: Blue",
+        Image,
+        Continuous,
+        ReadWrite,
+    ),
+    code(0x71, "Backlight Level: Blue", Image, Continuous, ReadWrite),
+    nc(0x72, "Gamma", Image, ReadWrite, &[]),
+    code(0x73, "LUT Size", Image, Table, ReadOnly),
+    code(0x74, "Single Point LUT Operation", Image, Table, ReadWrite),
+    code(0x75, "Block LUT Operation", Image, Table, ReadWrite),
+    code(
+        0x76,
+        "Remote Procedure Call",
+        Miscellaneous,
+        Table,
+        WriteOnly,
+    ),
+    code(
+        0x78,
+        "Display Identification Operation",
+        Miscellaneous,
+        Table,
+        ReadOnly,
+    ),
+    code(0x7A, "Adjust Focal Plane", Image, Continuous, ReadWrite),
+    code(0x7C, "Adjust Zoom", Image, Continuous, ReadWrite),
+    code(0x7E, "Trapezoid", Geometry, Continuous, ReadWrite),
+    code(0x80, "Keystone", Geometry, Continuous, ReadWrite),
+    nc(0x82, "Horizontal Mirror", Image, ReadWrite, MIRROR),
+    nc(0x84, "Vertical Mirror", Image, ReadWrite, MIRROR),
+    nc(0x86, "Display Scaling", Image, ReadWrite, DISPLAY_SCALING),
+    code(0x87, "Sharpness", Image, Continuous, ReadWrite),
+    code(
+        0x88,
+        "Velocity Scan Modulation",
+        Image,
+        Continuous,
+        ReadWrite,
+    ),
+    code(0x8A, "Color Saturation", Image, Continuous, ReadWrite),
+    nc(0x8B, "TV Channel Up/Down", Miscellaneous, WriteOnly, &[]),
+    code(0x8C, "TV Sharpness", Image, Continuous, ReadWrite),
+    nc(0x8D, "Audio Mute", Audio, ReadWrite, MUTE),
+    code(0x8E, "TV Contrast", Image, Continuous, ReadWrite),
+    code(0x8F, "Audio Treble", Audio, Continuous, ReadWrite),
+    code(0x90, "Hue", Image, Continuous, ReadWrite),
+    code(0x91, "Audio Bass", Audio, Continuous, ReadWrite),
+    code(0x92, "TV Black Level", Image, Continuous, ReadWrite),
+    code(0x93, "Audio Balance L/R", Audio, Continuous, ReadWrite),
+    nc(
+        0x94,
+        "Audio Processor Mode",
+        Audio,
+        ReadWrite,
+        AUDIO_PROCESSOR_MODE,
+    ),
+    code(0x95, "Window Position (TL_X)", Image, Continuous, ReadWrite),
+    code(0x96, "Window Position (TL_Y)", Image, Continuous, ReadWrite),
+    code(0x97, "Window Position (BR_X)", Image, Continuous, ReadWrite),
+    code(0x98, "Window Position (BR_Y)", Image, Continuous, ReadWrite),
+    nc(0x99, "Window Control", Image, ReadWrite, ON_OFF),
+    code(0x9A, "Window Background", Image, Continuous, ReadWrite),
+    code(0x9B, "6 Axis Hue: Red", Image, Continuous, ReadWrite),
+    code(0x9C, "6 Axis Hue: Yellow", Image, Continuous, ReadWrite),
+    code(0x9D, "6 Axis Hue: Green", Image, Continuous, ReadWrite),
+    code(0x9E, "6 Axis Hue: Cyan", Image, Continuous, ReadWrite),
+    code(0x9F, "6 Axis Hue: Blue", Image, Continuous, ReadWrite),
+    code(0xA0, "6 Axis Hue: Magenta", Image, Continuous, ReadWrite),
+    nc(0xA2, "Auto Setup On/Off", Image, WriteOnly, ON_OFF),
+    code(0xA4, "Window Mask Control", Image, Table, ReadWrite),
+    nc(0xA5, "Window Select", Image, ReadWrite, &[]),
+    nc(
+        0xAA,
+        "Screen Orientation",
+        Display,
+        ReadOnly,
+        SCREEN_ORIENTATION,
+    ),
+    code(0xAC, "Horizontal Frequency", Display, Continuous, ReadOnly),
+    code(0xAE, "Vertical Frequency", Display, Continuous, ReadOnly),
+    nc(0xB0, "Settings", Preset, WriteOnly, SETTINGS),
+    nc(
+        0xB2,
+        "Flat Panel Sub-Pixel Layout",
+        Display,
+        ReadOnly,
+        SUBPIXEL_LAYOUT,
+    ),
+    code(0xB4, "Source Timing Mode", Display, Table, ReadWrite),
+    nc(
+        0xB6,
+        "Display Technology Type",
+        Display,
+        ReadOnly,
+        DISPLAY_TECHNOLOGY,
+    ),
+    nc(0xB7, "Monitor Status", Dpvl, ReadOnly, MONITOR_STATUS),
+    code(0xB8, "Packet Count", Dpvl, Continuous, ReadWrite),
+    code(0xB9, "Monitor X Origin", Dpvl, Continuous, ReadWrite),
+    code(0xBA, "Monitor Y Origin", Dpvl, Continuous, ReadWrite),
+    code(0xBB, "Header Error Count", Dpvl, Continuous, ReadWrite),
+    code(0xBC, "Body CRC Error Count", Dpvl, Continuous, ReadWrite),
+    code(0xBD, "Client ID", Dpvl, Continuous, ReadWrite),
+    nc(0xBE, "Link Control", Dpvl, ReadWrite, LINK_CONTROL),
+    code(0xC0, "Display Usage Time", Display, Continuous, ReadOnly),
+    code(
+        0xC2,
+        "Display Descriptor Length",
+        Display,
+        Continuous,
+        ReadOnly,
+    ),
+    code(
+        0xC3,
+        "Transmit Display Descriptor",
+        Display,
+        Table,
+        ReadWrite,
+    ),
+    nc(
+        0xC4,
+        "Enable Display of Display Descriptor",
+        Display,
+        ReadWrite,
+        &[],
+    ),
+    nc(0xC6, "Application Enable Key", Display, ReadOnly, &[]),
+    nc(0xC8, "Display Controller Type", Display, ReadOnly, &[]),
+    code(
+        0xC9,
+        "Display Firmware Level",
+        Display,
+        Continuous,
+        ReadOnly,
+    ),
+    nc(0xCA, "OSD", Display, ReadWrite, OSD),
+    nc(0xCC, "OSD Language", Display, ReadWrite, OSD_LANGUAGES),
+    nc(0xCD, "Status Indicators", Miscellaneous, ReadWrite, &[]),
+    nc(0xCE, "Auxiliary Display Size", Miscellaneous, ReadOnly, &[]),
+    code(
+        0xCF,
+        "Auxiliary Display Data",
+        Miscellaneous,
+        Table,
+        WriteOnly,
+    ),
+    nc(
+        0xD0,
+        "Output Select",
+        Miscellaneous,
+        ReadWrite,
+        INPUT_SOURCES,
+    ),
+    code(0xD2, "Asset Tag", Miscellaneous, Table, ReadWrite),
+    nc(
+        0xD4,
+        "Stereo Video Mode",
+        Image,
+        ReadWrite,
+        STEREO_VIDEO_MODE,
+    ),
+    nc(0xD6, "Power Mode", Display, ReadWrite, POWER_MODES),
+    nc(
+        0xD7,
+        "Auxiliary Power Output",
+        Miscellaneous,
+        ReadWrite,
+        AUXILIARY_POWER,
+    ),
+    nc(0xDA, "Scan Mode", Image, ReadWrite, SCAN_MODE),
+    nc(0xDB, "Image Mode", Image, ReadWrite, IMAGE_MODE),
+    nc(
+        0xDC,
+        "Display Application",
+        Image,
+        ReadWrite,
+        DISPLAY_APPLICATION,
+    ),
+    code(0xDE, "Scratch Pad", Miscellaneous, Table, ReadWrite),
+    nc(0xDF, "VCP Version", Display, ReadOnly, &[]),
+];
+
+/// Returns the description of `code`; codes from `0xE0` are manufacturer specific.
+pub fn lookup(code: u8) -> Option<&'static VcpCode> {
+    VCP_CODES
+        .binary_search_by_key(&code, |vcp| vcp.code)
+        .ok()
+        .map(|i| &VCP_CODES[i])
+}
+
+pub fn group(code: u8) -> Option<Group> {
+    match code {
+        0xE0..=0xFF => Some(Manufacturer),
+        _ => lookup(code).map(|vcp| vcp.group),
+    }
+}
+
+/// Returns the name of `value` if `code` is a non-continuous code that names it.
+pub fn value_name(code: u8, value: u32) -> Option<&'static str> {
+    lookup(code)?.value_name(value)
+}
+
+/// Finds a code by name, ignoring case and everything but letters and digits.
+pub fn find(name: &str) -> Option<&'static VcpCode> {
+    fn key(s: &str) -> impl Iterator<Item = char> + '_ {
+        s.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|ch| ch.to_ascii_lowercase())
+    }
+    VCP_CODES.iter().find(|vcp| key(vcp.name).eq(key(name)))
+}
@@ -0,0 +1,80 @@
+//! Writes that are read back until the monitor reports the requested value.
+//!
+//! Monitors acknowledge writes they then ignore, e.g. while an OSD menu is open or in a picture
+//! mode that locks the setting, and some apply a write only after a while. [`set`] therefore polls
+//! the feature until it matches or a deadline passes, and tells which happened.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::vcp::Kind;
+use crate::{Feature, Monitor, Reply, Result};
+
+#[derive(Debug, Clone)]
+pub struct VerifyTiming {
+    /// How long to wait between reads that do not match; the pacing of the monitor comes on top.
+    pub poll: Duration,
+    /// How long after the write to give up on the value matching.
+    pub timeout: Duration,
+}
+
+impl Default for VerifyTiming {
+    fn default() -> Self {
+        VerifyTiming {
+            poll: Duration::from_millis(100),
+            timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The monitor reports the requested value.
+    Verified(Reply),
+    /// The monitor still reports another value at the deadline.
+    Mismatch { requested: u32, actual: u32 },
+}
+
+/// Whether `reply` shows `value`. Only the low byte of non-continuous values counts, as some
+/// monitors put garbage in the high byte.
+fn matches(feature: Feature, reply: &Reply, value: u32) -> bool {
+    match feature.describe() {
+        Some(code) if code.kind == Kind::NonContinuous => reply.current & 0xFF == value & 0xFF,
+        _ => reply.current == value,
+    }
+}
+
+/// Sets `feature` of `monitor` to `value` and reads it back until it matches.
+///
+/// A failed write is returned as is. Reads that fail are retried until the deadline, and the last
+/// failure is returned if none succeeded.
+pub fn set(
+    monitor: &Monitor,
+    feature: Feature,
+    value: u32,
+    timing: &VerifyTiming,
+) -> Result<Outcome> {
+    monitor.set_feature(feature, value)?;
+    let deadline = Instant::now() + timing.timeout;
+    let mut actual = None;
+    let mut error = None;
+    loop {
+        match monitor.get_feature(feature) {
+            Ok(reply) if matches(feature, &reply, value) => return Ok(Outcome::Verified(reply)),
+            Ok(reply) => actual = Some(reply.current),
+            Err(e) => error = Some(e),
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        thread::sleep(timing.poll);
+    }
+    match (actual, error) {
+        (Some(actual), _) => Ok(Outcome::Mismatch {
+            requested: value,
+            actual,
+        }),
+        (None, Some(e)) => Err(e),
+        (None, None) => unreachable!("the feature is read at least once"),
+    }
+}
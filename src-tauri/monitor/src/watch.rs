@@ -0,0 +1,96 @@
+//! Detection of monitors being connected and disconnected.
+//!
+//! Enumerating monitors is slow and, on Windows, opens new physical handles, so a [`Watcher`]
+//! polls [`MonitorBackend::generation`], which only reads the connector state, and tells when an
+//! enumeration is worth doing. [`merge`] then applies the enumeration to the monitors already
+//! known, keeping those still present.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::{Monitor, MonitorBackend};
+
+/// Hashes the state a backend derives its generation from.
+pub(crate) fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Remembers the generation of a backend between polls.
+#[derive(Debug, Default, Clone)]
+pub struct Watcher {
+    generation: Option<u64>,
+}
+
+impl Watcher {
+    pub fn new(backend: &dyn MonitorBackend) -> Watcher {
+        Watcher {
+            generation: backend.generation(),
+        }
+    }
+
+    /// Returns whether the monitors of `backend` may have changed since the previous poll.
+    /// Backends that cannot tell never report changes.
+    pub fn poll(&mut self, backend: &dyn MonitorBackend) -> bool {
+        let generation = backend.generation();
+        let changed = generation != self.generation;
+        self.generation = generation;
+        changed
+    }
+}
+
+/// The entries a [`merge`] added and removed.
+#[derive(Debug)]
+pub struct Changes<T> {
+    /// The indices of the new entries.
+    pub added: Vec<usize>,
+    pub removed: Vec<T>,
+}
+
+impl<T> Changes<T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+fn same(a: &Monitor, b: &Monitor) -> bool {
+    a.id == b.id && a.identity == b.identity
+}
+
+/// Replaces the entries of `current` by the monitors of `fresh`, in the order of `fresh`.
+///
+/// A monitor is still present if both its OS path and its identity are unchanged; its entry is
+/// kept with its handle and whatever state `T` holds, and the fresh duplicate is dropped. New
+/// monitors are wrapped into entries with `wrap`. The entries of monitors no longer present are
+/// returned.
+pub fn merge<T>(
+    current: &mut Vec<T>,
+    fresh: Vec<Monitor>,
+    monitor: impl Fn(&T) -> &Monitor,
+    mut wrap: impl FnMut(Monitor) -> T,
+) -> Changes<T> {
+    let mut added = Vec::new();
+    let mut old: Vec<Option<T>> = current.drain(..).map(Some).collect();
+    for fresh in fresh {
+        let kept = old
+            .iter_mut()
+            .find(|entry| {
+                entry
+                    .as_ref()
+                    .is_some_and(|entry| same(monitor(entry), &fresh))
+            })
+            .and_then(Option::take);
+        current.push(match kept {
+            Some(entry) => entry,
+            None => {
+                added.push(current.len());
+                wrap(fresh)
+            }
+        });
+    }
+    Changes {
+        added,
+        removed: old.into_iter().flatten().collect(),
+    }
+}
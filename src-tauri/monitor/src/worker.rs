@@ -0,0 +1,370 @@
+//! A thread per monitor that owns it and runs the commands sent to it in order.
+//!
+//! DDC/CI commands block for tens to hundreds of milliseconds, so a slider dragged across a
+//! monitor queues far more writes than the monitor can take. The worker therefore takes every
+//! command queued while it was busy at once and coalesces them: the writes to a feature are
+//! collapsed into the latest one, and reads of a feature are answered by a single read, or by the
+//! read back of a pending write. Commands given as closures are run as they are, and nothing is
+//! coalesced across those run for the user; closures run in the background, e.g. to poll, are
+//! passed over like reads.
+//!
+//! Writes are run before the reads queued with them, and the workers of monitors sharing a
+//! [`Bus`] take turns on it, those with a write to send first. Workers on different buses run in
+//! parallel.
+//!
+//! A command that blocks, e.g. on a monitor whose I2C bus hangs, cannot be interrupted. Each one
+//! has a deadline instead, and past it [`Worker::is_unresponsive`] tells callers to stop waiting.
+//! The deadline starts once the worker has the bus, so a monitor waiting its turn, e.g. a poll
+//! behind the writes to another monitor, is not taken for unresponsive.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Error, Feature, Monitor, Reply, Result};
+
+/// Receives the result of a read or write.
+pub type Done = Box<dyn FnOnce(Result<Reply>) + Send>;
+
+enum Task {
+    Read {
+        feature: Feature,
+        done: Vec<Done>,
+    },
+    /// Writes a feature and reads it back.
+    Write {
+        feature: Feature,
+        value: u32,
+        done: Vec<Done>,
+    },
+    Run {
+        timeout: Duration,
+        priority: Priority,
+        run: Box<dyn FnOnce(&Monitor) + Send>,
+    },
+}
+
+impl Task {
+    fn feature(&self) -> Option<Feature> {
+        match self {
+            Task::Read { feature, .. } | Task::Write { feature, .. } => Some(*feature),
+            Task::Run { .. } => None,
+        }
+    }
+
+    /// Reads are taken for polling, writes are what the user asked for, and closures say which.
+    fn priority(&self) -> Priority {
+        match self {
+            Task::Read { .. } => Priority::Background,
+            Task::Write { .. } => Priority::Interactive,
+            Task::Run { priority, .. } => *priority,
+        }
+    }
+
+    fn is_barrier(&self) -> bool {
+        matches!(
+            self,
+            Task::Run {
+                priority: Priority::Interactive,
+                ..
+            }
+        )
+    }
+}
+
+/// Copies an error to hand it to more than one caller; the source is kept as text.
+fn duplicate(e: &Error) -> Error {
+    match e.get_ref() {
+        Some(source) => Error::new(e.kind(), source.to_string()),
+        None => Error::from(e.kind()),
+    }
+}
+
+fn finish(done: Vec<Done>, result: Result<Reply>) {
+    let mut done = done.into_iter();
+    let Some(last) = done.next_back() else {
+        return;
+    };
+    for done in done {
+        done(result.as_ref().copied().map_err(duplicate));
+    }
+    last(result);
+}
+
+/// Merges the tasks queued at once; see the [module docs](self).
+fn coalesce(tasks: Vec<Task>) -> Vec<Task> {
+    let mut merged: Vec<Task> = Vec::new();
+    // the tasks before the last interactive closure are not merged into
+    let mut barrier = 0;
+    for task in tasks {
+        match task {
+            Task::Write {
+                feature,
+                value,
+                done,
+            } => {
+                let pending = merged[barrier..].iter_mut().find(
+                    |task| matches!(task, Task::Write { feature: pending, .. } if *pending == feature),
+                );
+                match pending {
+                    Some(Task::Write {
+                        value: pending,
+                        done: waiting,
+                        ..
+                    }) => {
+                        *pending = value;
+                        waiting.extend(done);
+                    }
+                    _ => merged.push(Task::Write {
+                        feature,
+                        value,
+                        done,
+                    }),
+                }
+            }
+            Task::Read { feature, done } => {
+                let pending = merged[barrier..]
+                    .iter_mut()
+                    .rev()
+                    .find(|task| task.feature() == Some(feature));
+                match pending {
+                    Some(Task::Read { done: waiting, .. } | Task::Write { done: waiting, .. }) => {
+                        waiting.extend(done)
+                    }
+                    _ => merged.push(Task::Read { feature, done }),
+                }
+            }
+            Task::Run { .. } => {
+                let is_barrier = task.is_barrier();
+                merged.push(task);
+                if is_barrier {
+                    barrier = merged.len();
+                }
+            }
+        }
+    }
+    merged
+}
+
+/// Picks the task to run next: the first write queued before the next interactive closure, so
+/// that writes are not held up by reads or polling, or else the first task.
+fn next(tasks: &[Task]) -> usize {
+    tasks
+        .iter()
+        .take_while(|task| !task.is_barrier())
+        .position(|task| matches!(task, Task::Write { .. }))
+        .unwrap_or(0)
+}
+
+/// Whether a command is waited on by the user or polls in the background.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Background,
+    Interactive,
+}
+
+#[derive(Debug, Default)]
+struct BusState {
+    busy: bool,
+    /// The number of interactive commands waiting, which background ones let go first.
+    interactive: usize,
+}
+
+/// A bus shared by the workers of several monitors, which run one command on it at a time.
+#[derive(Debug, Default)]
+pub struct Bus {
+    state: Mutex<BusState>,
+    released: Condvar,
+}
+
+impl Bus {
+    pub fn new() -> Bus {
+        Bus::default()
+    }
+
+    /// Blocks until the bus is free for a command of `priority`.
+    pub fn lock(&self, priority: Priority) -> BusGuard<'_> {
+        let mut state = self.state.lock().unwrap();
+        match priority {
+            Priority::Interactive => {
+                state.interactive += 1;
+                state = self.wait(state, |state| state.busy);
+                state.interactive -= 1;
+            }
+            Priority::Background => {
+                state = self.wait(state, |state| state.busy || state.interactive > 0);
+            }
+        }
+        state.busy = true;
+        BusGuard { bus: self }
+    }
+
+    fn wait<'a>(
+        &self,
+        state: MutexGuard<'a, BusState>,
+        blocked: impl FnMut(&mut BusState) -> bool,
+    ) -> MutexGuard<'a, BusState> {
+        self.released.wait_while(state, blocked).unwrap()
+    }
+}
+
+/// Frees the bus when dropped.
+#[derive(Debug)]
+pub struct BusGuard<'a> {
+    bus: &'a Bus,
+}
+
+impl Drop for BusGuard<'_> {
+    fn drop(&mut self) {
+        self.bus.state.lock().unwrap().busy = false;
+        self.bus.released.notify_all();
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// When the running command is due, if one is running.
+    deadline: Mutex<Option<Instant>>,
+}
+
+#[derive(Debug)]
+pub struct Worker {
+    monitor: Arc<Monitor>,
+    tasks: Sender<Task>,
+    state: Arc<State>,
+    timeout: Duration,
+}
+
+impl Worker {
+    /// How long a read or write may take, retries and pacing included, by default.
+    pub const TIMEOUT: Duration = Duration::from_secs(3);
+
+    /// Spawns a worker for a monitor on a bus of its own.
+    pub fn spawn(monitor: Monitor) -> Worker {
+        Worker::on_bus(monitor, Arc::default(), Worker::TIMEOUT)
+    }
+
+    /// Spawns a worker whose reads and writes are due after `timeout`.
+    pub fn with_timeout(monitor: Monitor, timeout: Duration) -> Worker {
+        Worker::on_bus(monitor, Arc::default(), timeout)
+    }
+
+    /// Spawns a worker that takes turns on `bus` with the other workers given it.
+    pub fn on_bus(monitor: Monitor, bus: Arc<Bus>, timeout: Duration) -> Worker {
+        let monitor = Arc::new(monitor);
+        let (tasks, queue) = mpsc::channel();
+        let state = Arc::<State>::default();
+        {
+            let monitor = monitor.clone();
+            let state = state.clone();
+            thread::spawn(move || work(&monitor, &queue, &bus, &state, timeout));
+        }
+        Worker {
+            monitor,
+            tasks,
+            state,
+            timeout,
+        }
+    }
+
+    /// The monitor, for what does not send commands to it, such as its identity.
+    pub fn monitor(&self) -> &Monitor {
+        &self.monitor
+    }
+
+    /// Whether the running command is past its deadline.
+    pub fn is_unresponsive(&self) -> bool {
+        self.state
+            .deadline
+            .lock()
+            .unwrap()
+            .is_some_and(|deadline| Instant::now() > deadline)
+    }
+
+    /// Reads `feature`; reads queued together are sent once.
+    pub fn read(&self, feature: Feature, done: impl FnOnce(Result<Reply>) + Send + 'static) {
+        self.send(Task::Read {
+            feature,
+            done: vec![Box::new(done)],
+        });
+    }
+
+    /// Writes `value` to `feature` and reads it back. Of the writes to a feature queued together
+    /// only the last is sent, and all of them are answered with its read back.
+    pub fn write(
+        &self,
+        feature: Feature,
+        value: u32,
+        done: impl FnOnce(Result<Reply>) + Send + 'static,
+    ) {
+        self.send(Task::Write {
+            feature,
+            value,
+            done: vec![Box::new(done)],
+        });
+    }
+
+    /// Runs `run` on the worker, due after `timeout`. An interactive closure runs in order with
+    /// the reads and writes; a background one lets the writes queued with it go first.
+    pub fn run(
+        &self,
+        timeout: Duration,
+        priority: Priority,
+        run: impl FnOnce(&Monitor) + Send + 'static,
+    ) {
+        self.send(Task::Run {
+            timeout,
+            priority,
+            run: Box::new(run),
+        });
+    }
+
+    fn send(&self, task: Task) {
+        // the worker only stops once the sender is dropped
+        let _ = self.tasks.send(task);
+    }
+
+    /// How long the reads and writes of this worker may take.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+fn work(monitor: &Monitor, queue: &Receiver<Task>, bus: &Bus, state: &State, timeout: Duration) {
+    let mut pending = Vec::new();
+    loop {
+        if pending.is_empty() {
+            match queue.recv() {
+                Ok(task) => pending.push(task),
+                Err(_) => return,
+            }
+        }
+        // commands queued while the last one ran may be merged into the pending ones
+        pending.extend(queue.try_iter());
+        pending = coalesce(pending);
+        let task = pending.remove(next(&pending));
+        let due = match &task {
+            Task::Run { timeout, .. } => *timeout,
+            _ => timeout,
+        };
+        let guard = bus.lock(task.priority());
+        *state.deadline.lock().unwrap() = Some(Instant::now() + due);
+        match task {
+            Task::Read { feature, done } => finish(done, monitor.get_feature(feature)),
+            Task::Write {
+                feature,
+                value,
+                done,
+            } => {
+                let result = monitor
+                    .set_feature(feature, value)
+                    .and_then(|_| monitor.get_feature(feature));
+                finish(done, result);
+            }
+            Task::Run { run, .. } => run(monitor),
+        }
+        drop(guard);
+        *state.deadline.lock().unwrap() = None;
+    }
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::fake_sysfs;
+use monitor::backlight::BacklightBackend;
+use monitor::{ErrorKind, Feature, Interface, MonitorBackend, Reply};
+
+fn add_device(root: &Path, name: &str, kind: &str, brightness: u32, max: u32) {
+    let dir = root.join("class/backlight").join(name);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("type"), format!("{kind}\n")).unwrap();
+    fs::write(dir.join("brightness"), format!("{brightness}\n")).unwrap();
+    fs::write(dir.join("actual_brightness"), format!("{brightness}\n")).unwrap();
+    fs::write(dir.join("max_brightness"), format!("{max}\n")).unwrap();
+}
+
+#[test]
+fn prefers_firmware_devices() {
+    let root = fake_sysfs("prefer");
+    add_device(&root, "intel_backlight", "raw", 9600, 19200);
+    add_device(&root, "acpi_video0", "firmware", 50, 100);
+    let monitors = BacklightBackend::new(&root).enumerate();
+    assert_eq!(monitors.len(), 1);
+    assert_eq!(monitors[0].id, "acpi_video0");
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn get_and_set_luminance() {
+    let root = fake_sysfs("rw");
+    add_device(&root, "intel_backlight", "raw", 9600, 19200);
+    let monitors = BacklightBackend::new(&root).enumerate();
+    let monitor = &monitors[0];
+    assert_eq!(
+        monitor.get_feature(Feature::Luminance).unwrap(),
+        Reply {
+            current: 9600,
+            maximum: 19200,
+            source: Interface::Backlight,
+        }
+    );
+    monitor.set_feature(Feature::Luminance, 30000).unwrap();
+    let brightness = root.join("class/backlight/intel_backlight/brightness");
+    assert_eq!(fs::read_to_string(brightness).unwrap(), "19200");
+    let err = monitor.get_feature(Feature::Contrast).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotSupported);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn missing_class_is_empty() {
+    let root = fake_sysfs("missing");
+    assert!(BacklightBackend::new(&root).enumerate().is_empty());
+}
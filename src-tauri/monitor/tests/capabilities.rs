@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::Path;
+
+use monitor::capabilities::{Capabilities, MccsVersion};
+
+fn fixture(name: &str) -> Capabilities {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/capabilities")
+        .join(name);
+    Capabilities::parse(&fs::read_to_string(path).unwrap())
+}
+
+#[test]
+fn whole_corpus_declares_luminance() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/capabilities");
+    for entry in fs::read_dir(dir).unwrap() {
+        let name = entry.unwrap().file_name();
+        let caps = fixture(name.to_str().unwrap());
+        assert!(caps.supports(0x10), "{name:?}: {caps:?}");
+        assert_eq!(caps.protocol.as_deref(), Some("monitor"), "{name:?}");
+    }
+}
+
+#[test]
+fn well_formed() {
+    let caps = fixture("dell_u2415.txt");
+    assert_eq!(caps.kind.as_deref(), Some("LCD"));
+    assert_eq!(caps.model.as_deref(), Some("U2415"));
+    assert_eq!(caps.commands, [0x01, 0x02, 0x03, 0x07, 0x0C, 0xE3, 0xF3]);
+    assert_eq!(caps.values(0x60), [0x01, 0x0F, 0x11]);
+    assert_eq!(
+        caps.values(0x14),
+        [0x01, 0x04, 0x05, 0x06, 0x08, 0x09, 0x0B, 0x0C]
+    );
+    assert!(caps.values(0x12).is_empty());
+    assert!(caps.supports(0xFD));
+    assert!(!caps.supports(0x62));
+    assert_eq!(caps.mccs_version, Some(MccsVersion { major: 2, minor: 1 }));
+    assert_eq!(caps.extensions["mswhql"], "1");
+    assert_eq!(caps.extensions["asset_eep"], "40");
+}
+
+#[test]
+fn bare_word_before_key() {
+    let caps = fixture("samsung_bare_model.txt");
+    assert_eq!(caps.commands.len(), 7);
+    assert_eq!(caps.values(0x60), [0x01, 0x03, 0x04, 0x05]);
+    assert_eq!(caps.values(0xDC), [0x00, 0x01, 0x02, 0x03, 0x04, 0x05]);
+}
+
+#[test]
+fn concatenated_codes() {
+    let caps = fixture("acer_concatenated.txt");
+    for code in [
+        0x02, 0x10, 0x12, 0x14, 0x16, 0x18, 0x1A, 0x5A, 0x60, 0x62, 0xAC, 0xDF,
+    ] {
+        assert!(caps.supports(code), "{code:#04x}");
+    }
+    assert_eq!(caps.values(0xD6), [0x01, 0x04, 0x05]);
+}
+
+#[test]
+fn truncated() {
+    let caps = fixture("dell_truncated.txt");
+    assert_eq!(caps.model.as_deref(), Some("P2419H"));
+    assert_eq!(caps.values(0x14), [0x05, 0x08, 0x0B, 0x0C]);
+    assert_eq!(caps.mccs_version, None);
+}
+
+#[test]
+fn vendor_names_and_trailing_nulls() {
+    let caps = fixture("asus_vcpname.txt");
+    assert_eq!(caps.vcp_names[&0xE0], "Game Visual");
+    assert_eq!(caps.vcp_names[&0xE2], "Color Mode");
+    assert_eq!(caps.values(0xE3), [0x00, 0x01, 0x02, 0x03, 0x04]);
+    assert_eq!(caps.mccs_version, Some(MccsVersion { major: 2, minor: 2 }));
+}
+
+#[test]
+fn duplicate_vcp_entries_merge() {
+    let caps = fixture("lg_duplicate_vcp.txt");
+    assert!(caps.supports(0x10));
+    assert!(caps.supports(0xE9));
+    assert_eq!(caps.values(0x60), [0x0F, 0x10, 0x11, 0x12]);
+    assert_eq!(caps.extensions["mswhql"], "1");
+}
+
+#[test]
+fn garbage() {
+    assert_eq!(Capabilities::parse(""), Capabilities::default());
+    assert_eq!(
+        Capabilities::parse("\u{FFFD}\u{FFFD}"),
+        Capabilities::default()
+    );
+    assert_eq!(Capabilities::parse("((((vcp(10"), Capabilities::default());
+}
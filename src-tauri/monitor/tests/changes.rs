@@ -0,0 +1,62 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use common::{enumerate, reply};
+use monitor::changes::Changes;
+use monitor::mock::MockMonitor;
+use monitor::Feature;
+
+fn codes(codes: &[u8]) -> Changes {
+    Changes::Codes(codes.iter().copied().collect::<BTreeSet<_>>())
+}
+
+#[test]
+fn reported_changes() {
+    let mock = MockMonitor::new("MOCK#A")
+        .feature(Feature::Luminance, reply(50, 100))
+        .feature(Feature::Contrast, reply(50, 100))
+        .report_changes();
+    let monitor = enumerate(mock.clone());
+    assert_eq!(monitor.changes().unwrap(), codes(&[]));
+
+    mock.change(Feature::Luminance, 70);
+    mock.change(Feature::Contrast, 40);
+    mock.change(Feature::Luminance, 75);
+    assert_eq!(monitor.changes().unwrap(), codes(&[0x10, 0x12]));
+    assert_eq!(
+        monitor.get_feature(Feature::Luminance).unwrap(),
+        reply(75, 100)
+    );
+    // the changes were acknowledged
+    assert_eq!(monitor.changes().unwrap(), codes(&[]));
+}
+
+#[test]
+fn unknown_changes() {
+    let monitor = enumerate(MockMonitor::new("MOCK#A").feature(Feature::Luminance, reply(50, 100)));
+    assert_eq!(monitor.changes().unwrap(), Changes::Unknown);
+
+    // changes flagged without a FIFO to tell which
+    let mock = MockMonitor::new("MOCK#B").feature(Feature::from(0x02), reply(0x02, 0xFF));
+    let monitor = enumerate(mock.clone());
+    assert_eq!(monitor.changes().unwrap(), Changes::Unknown);
+    assert_eq!(mock.value(Feature::from(0x02)).unwrap().current, 0x01);
+
+    // values MCCS does not define for the flag
+    for flag in [0x00, 0xFF] {
+        let monitor =
+            enumerate(MockMonitor::new("MOCK#C").feature(Feature::from(0x02), reply(flag, 0xFF)));
+        assert_eq!(monitor.changes().unwrap(), Changes::Unknown);
+    }
+}
+
+#[test]
+fn endless_fifo() {
+    let monitor = enumerate(
+        MockMonitor::new("MOCK#A")
+            .feature(Feature::from(0x02), reply(0x02, 0xFF))
+            .feature(Feature::from(0x52), reply(0x10, 0xFF)),
+    );
+    assert_eq!(monitor.changes().unwrap(), codes(&[0x10]));
+}
@@ -0,0 +1,213 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn cli_against_mock_backend() {
+    let output = Command::new(env!("CARGO_BIN_EXE_list-monitors"))
+        .env("MONITOR_BACKEND", "mock")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("MOCK#EXTERNAL#1"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "contrast"])
+        .env("MONITOR_BACKEND", "mock")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("current: 50"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "capabilities"])
+        .env("MONITOR_BACKEND", "mock")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("vcp: {"));
+}
+
+#[test]
+fn cli_prints_pacing() {
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "pacing", "contrast"])
+        .env("MONITOR_BACKEND", "mock")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("commands: 64"), "{stdout}");
+    assert!(stdout.contains("given_up: 0"), "{stdout}");
+}
+
+#[test]
+fn cli_exits_on_mismatch() {
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "luminance", "70", "--verify"])
+        .env("MONITOR_BACKEND", "mock")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("current: 70"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "luminance", "150", "--verify"])
+        .env("MONITOR_BACKEND", "mock")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("requested 150, the monitor reports 100"));
+}
+
+#[test]
+fn cli_switches_input() {
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "input", "hdmi1"])
+        .env("MONITOR_BACKEND", "mock")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("(HDMI-1)"), "{stdout}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "input", "vga1"])
+        .env("MONITOR_BACKEND", "mock")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn cli_prints_edid() {
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "edid"])
+        .env("MONITOR_BACKEND", "mock")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("manufacturer: \"MCK\""), "{stdout}");
+}
+
+#[test]
+fn cli_accepts_identity() {
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MCK-0001-00000001", "contrast"])
+        .env("MONITOR_BACKEND", "mock")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn cli_records_and_replays() {
+    let path = std::env::temp_dir().join(format!("monitor-trace-{}.jsonl", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "volume", "45"])
+        .env("MONITOR_BACKEND", "mock")
+        .env("MONITOR_TRACE", &path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let trace = fs::read_to_string(&path).unwrap();
+    assert!(trace.contains("\"op\":\"setFeature\",\"code\":98,\"value\":45"));
+
+    // the replay is served by the identity, as the bug report would name the monitor
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MCK-0001-00000001", "edid"])
+        .env("MONITOR_REPLAY", &path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Mock Monitor"));
+    fs::remove_file(&path).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MCK-0001-00000001", "edid"])
+        .env("MONITOR_REPLAY", &path)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("failed to read trace"), "{stderr}");
+}
+
+#[test]
+fn cli_simulation() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/simulation.toml"
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_list-monitors"))
+        .arg("--simulate")
+        .arg(path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("SIM#DELL#0"));
+    assert!(stdout.contains("SIM#LOCKED#1"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .arg("--simulate")
+        .arg(path)
+        .args(["DEL-A0F0-00000007", "luminance", "95", "--verify"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("requested 95, the monitor reports 90"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_list-monitors"))
+        .args(["--simulate", "missing.toml"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("failed to read simulation missing.toml"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn cli_user_quirks() {
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "contrast"])
+        .env("MONITOR_BACKEND", "mock")
+        .env(
+            "MONITOR_QUIRKS",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/quirks.toml"),
+        )
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr, "not supported\n");
+
+    // a broken user file is reported and left out
+    let path = std::env::temp_dir().join(format!("monitor-quirks-{}.toml", std::process::id()));
+    fs::write(&path, "[[monitor]]\nproduct = 1").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_monitor-config"))
+        .args(["MOCK#EXTERNAL#1", "contrast"])
+        .env("MONITOR_BACKEND", "mock")
+        .env("MONITOR_QUIRKS", &path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let message = format!(
+        "ignoring the quirks of the user: invalid input: invalid quirks {}",
+        path.display()
+    );
+    assert!(stderr.starts_with(&message), "{stderr}");
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,31 @@
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use monitor::mock::{MockBackend, MockMonitor};
+use monitor::{Interface, Monitor, MonitorBackend, Reply};
+
+pub fn reply(current: u32, maximum: u32) -> Reply {
+    Reply {
+        current,
+        maximum,
+        source: Interface::DDCCI,
+    }
+}
+
+/// Enumerates a mock backend holding just `monitor`.
+pub fn enumerate(monitor: MockMonitor) -> Monitor {
+    MockBackend::new()
+        .with_monitor(monitor)
+        .enumerate()
+        .pop()
+        .unwrap()
+}
+
+/// An empty directory standing in for `/sys`; `name` must be unique among the tests.
+pub fn fake_sysfs(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("monitor-sysfs-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    root
+}
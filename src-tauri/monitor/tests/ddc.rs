@@ -0,0 +1,114 @@
+use monitor::ddc::{
+    decode, decode_request, encode_response, DecodeError, Request, Response, TimingReport,
+    VcpReply, VcpType,
+};
+
+#[test]
+fn encode_requests() {
+    assert_eq!(
+        Request::GetVcp { code: 0x10 }.encode(),
+        [0x51, 0x82, 0x01, 0x10, 0xAC]
+    );
+    assert_eq!(
+        Request::SetVcp {
+            code: 0x10,
+            value: 50
+        }
+        .encode(),
+        [0x51, 0x84, 0x03, 0x10, 0x00, 0x32, 0x9A]
+    );
+    assert_eq!(
+        Request::Capabilities { offset: 32 }.encode(),
+        [0x51, 0x83, 0xF3, 0x00, 0x20, 0x6F]
+    );
+    assert_eq!(Request::SaveSettings.encode(), [0x51, 0x81, 0x0C, 0xB2]);
+}
+
+#[test]
+fn decode_vcp_reply() {
+    let bytes = [
+        0x6E, 0x88, 0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32, 0xF2, 0xFF, 0xFF,
+    ];
+    assert_eq!(
+        decode(&bytes).unwrap(),
+        Response::Vcp(VcpReply {
+            code: 0x10,
+            supported: true,
+            kind: VcpType::SetParameter,
+            maximum: 100,
+            current: 50,
+        })
+    );
+}
+
+#[test]
+fn decode_null_message() {
+    assert_eq!(decode(&[0x6E, 0x80, 0xBE]).unwrap(), Response::Null);
+}
+
+#[test]
+fn decode_errors() {
+    let mut bytes = [
+        0x6E, 0x88, 0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32, 0xF2,
+    ];
+    assert_eq!(
+        decode(&bytes[..6]),
+        Err(DecodeError::Truncated {
+            expected: 11,
+            actual: 6
+        })
+    );
+    bytes[10] = 0;
+    assert_eq!(
+        decode(&bytes),
+        Err(DecodeError::Checksum {
+            expected: 0xF2,
+            actual: 0
+        })
+    );
+    assert_eq!(decode(&[0xFF, 0xFF, 0xFF]), Err(DecodeError::Source(0xFF)));
+    assert_eq!(decode(&[0x6E, 0x08, 0x00]), Err(DecodeError::Length(0x08)));
+}
+
+#[test]
+fn responses_round_trip() {
+    let responses = [
+        Response::Null,
+        Response::Vcp(VcpReply {
+            code: 0x60,
+            supported: false,
+            kind: VcpType::Momentary,
+            maximum: 0,
+            current: 0,
+        }),
+        Response::Capabilities {
+            offset: 64,
+            data: b"vcp(10 12)".to_vec(),
+        },
+        Response::Timing(TimingReport {
+            status: 0,
+            horizontal_frequency: 6750,
+            vertical_frequency: 6000,
+        }),
+    ];
+    for response in responses {
+        assert_eq!(decode(&encode_response(&response)).unwrap(), response);
+    }
+}
+
+#[test]
+fn requests_round_trip() {
+    let requests = [
+        Request::GetVcp { code: 0xD6 },
+        Request::SetVcp {
+            code: 0x60,
+            value: 0x0F,
+        },
+        Request::Capabilities { offset: 0 },
+        Request::Timing,
+        Request::SaveSettings,
+    ];
+    for request in requests {
+        assert_eq!(decode_request(&request.encode()).unwrap(), request);
+    }
+}
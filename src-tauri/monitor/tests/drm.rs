@@ -0,0 +1,110 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+
+use common::fake_sysfs;
+use monitor::backlight::BacklightBackend;
+use monitor::drm::{self, connector_type};
+use monitor::edid::Edid;
+use monitor::{ConnectorType, MonitorBackend};
+
+fn add_connector(root: &Path, name: &str, status: &str, enabled: &str) -> PathBuf {
+    let dir = root.join("class/drm").join(name);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("status"), format!("{status}\n")).unwrap();
+    fs::write(dir.join("enabled"), format!("{enabled}\n")).unwrap();
+    dir
+}
+
+#[test]
+fn connector_types() {
+    assert_eq!(
+        connector_type("card0-eDP-1"),
+        Some(ConnectorType::EmbeddedDisplayPort)
+    );
+    assert_eq!(connector_type("card1-HDMI-A-2"), Some(ConnectorType::Hdmi));
+    assert_eq!(
+        connector_type("card0-DP-3"),
+        Some(ConnectorType::DisplayPort)
+    );
+    assert_eq!(connector_type("card0-DVI-D-1"), Some(ConnectorType::Dvi));
+    assert_eq!(
+        connector_type("card0-Virtual-1"),
+        Some(ConnectorType::Other)
+    );
+    assert_eq!(connector_type("card0"), None);
+    assert_eq!(connector_type("renderD128"), None);
+    assert_eq!(connector_type("cardX-DP-1"), None);
+    assert!(ConnectorType::Lvds.is_builtin());
+    assert!(!ConnectorType::DisplayPort.is_builtin());
+}
+
+#[test]
+fn enumerate_connectors() {
+    let root = fake_sysfs("connectors");
+    let hdmi = add_connector(&root, "card0-HDMI-A-1", "connected", "enabled");
+    symlink("../../i2c-3", hdmi.join("ddc")).unwrap();
+    let dp = add_connector(&root, "card0-DP-1", "connected", "disabled");
+    fs::create_dir_all(dp.join("i2c-7")).unwrap();
+    add_connector(&root, "card0-DP-2", "disconnected", "disabled");
+    fs::create_dir_all(root.join("class/drm/card0")).unwrap();
+    fs::write(root.join("class/drm/version"), "drm 1.1.0\n").unwrap();
+
+    let connectors = drm::connectors(&root);
+    let names: Vec<_> = connectors.iter().map(|c| c.name.clone()).collect();
+    assert_eq!(names, ["card0-DP-1", "card0-DP-2", "card0-HDMI-A-1"]);
+    let (dp, hdmi) = (&connectors[0], &connectors[2]);
+    assert_eq!(dp.kind, ConnectorType::DisplayPort);
+    assert!(dp.connected && !dp.enabled);
+    assert_eq!(dp.ddc.as_deref(), Some("i2c-7".as_ref()));
+    assert!(!connectors[1].connected);
+    assert_eq!(connectors[1].ddc, None);
+    assert_eq!(hdmi.kind, ConnectorType::Hdmi);
+    assert!(hdmi.connected && hdmi.enabled);
+    assert_eq!(hdmi.ddc.as_deref(), Some("i2c-3".as_ref()));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn backlight_gets_the_panel_connector() {
+    let root = fake_sysfs("panel");
+    let panel = Edid {
+        manufacturer: "BOE".to_owned(),
+        product_code: 0x0987,
+        ..Default::default()
+    }
+    .encode();
+    add_connector(&root, "card0-DP-1", "connected", "enabled");
+    fs::write(
+        root.join("class/drm/card0-DP-1/edid"),
+        Edid::default().encode(),
+    )
+    .unwrap();
+    let edp = add_connector(&root, "card0-eDP-1", "connected", "enabled");
+    fs::write(edp.join("edid"), &panel).unwrap();
+    fs::create_dir_all(root.join("class/backlight/intel_backlight")).unwrap();
+
+    let monitors = BacklightBackend::new(&root).enumerate();
+    assert_eq!(monitors.len(), 1);
+    assert_eq!(
+        monitors[0].connector,
+        Some(ConnectorType::EmbeddedDisplayPort)
+    );
+    assert!(monitors[0].is_builtin());
+    assert_eq!(monitors[0].get_edid().unwrap().manufacturer, "BOE");
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn backlight_without_panel_connector() {
+    let root = fake_sysfs("nopanel");
+    fs::create_dir_all(root.join("class/backlight/acpi_video0")).unwrap();
+    let monitors = BacklightBackend::new(&root).enumerate();
+    assert_eq!(monitors[0].connector, Some(ConnectorType::Internal));
+    assert!(monitors[0].get_edid().is_err());
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,135 @@
+use std::fs;
+
+use monitor::backlight::BacklightBackend;
+use monitor::edid::{Chromaticity, DigitalInterface, Edid, EdidError, VideoInput};
+use monitor::mock::{MockBackend, MockMonitor};
+use monitor::{Feature, Interface, MonitorBackend, Reply};
+
+fn descriptor(tag: u8, text: &str) -> [u8; 18] {
+    let mut descriptor = [0x20; 18];
+    descriptor[..5].copy_from_slice(&[0, 0, 0, tag, 0]);
+    descriptor[5..5 + text.len()].copy_from_slice(text.as_bytes());
+    if text.len() < 13 {
+        descriptor[5 + text.len()] = b'\n';
+    }
+    descriptor
+}
+
+/// A base block modelled on a Dell U2415 with EDID 1.4.
+fn dell_u2415() -> Vec<u8> {
+    let mut block = vec![0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+    block.extend([0x10, 0xAC]); // DEL
+    block.extend(0xA0BAu16.to_le_bytes());
+    block.extend(0x3042_4C4Cu32.to_le_bytes());
+    block.extend([12, 27, 1, 4]); // week 12 of 2017, EDID 1.4
+    block.extend([0xA5, 52, 32, 0x78, 0x3A]); // 8-bit DisplayPort, 52x32 cm
+    block.extend([0xEE, 0x95, 0xA3, 0x54, 0x4C, 0x99, 0x26, 0x0F, 0x50, 0x54]);
+    block.resize(54, 0);
+    // a detailed timing for 1920x1200
+    block.extend([
+        0x28, 0x3C, 0x80, 0xA0, 0x70, 0xB0, 0x23, 0x40, 0x30, 0x20, 0x36, 0x00, 0x06, 0x44, 0x21,
+        0x00, 0x00, 0x1A,
+    ]);
+    block.extend(descriptor(0xFF, "CFV9N7BJ0LLB"));
+    block.extend(descriptor(0xFC, "DELL U2415"));
+    block.extend(descriptor(0xFD, ""));
+    block.push(1);
+    let sum = block.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+    block.push(sum.wrapping_neg());
+    block
+}
+
+#[test]
+fn parse_base_block() {
+    let edid = Edid::parse(&dell_u2415()).unwrap();
+    assert_eq!(edid.manufacturer, "DEL");
+    assert_eq!(edid.product_code, 0xA0BA);
+    assert_eq!(edid.serial_number, 0x3042_4C4C);
+    assert_eq!(
+        (edid.week, edid.year, edid.model_year),
+        (Some(12), 2017, false)
+    );
+    assert_eq!(edid.version, (1, 4));
+    assert_eq!(
+        edid.input,
+        VideoInput::Digital {
+            bit_depth: Some(8),
+            interface: Some(DigitalInterface::DisplayPort)
+        }
+    );
+    assert_eq!(edid.size, Some((52, 32)));
+    let Chromaticity { red, white, .. } = edid.chromaticity;
+    assert!((red.0 - 0.639).abs() < 0.001 && (red.1 - 0.330).abs() < 0.001);
+    assert!((white.0 - 0.313).abs() < 0.001 && (white.1 - 0.329).abs() < 0.001);
+    assert_eq!(edid.name.as_deref(), Some("DELL U2415"));
+    assert_eq!(edid.serial.as_deref(), Some("CFV9N7BJ0LLB"));
+    assert_eq!(edid.extensions, 1);
+}
+
+#[test]
+fn invalid_blocks() {
+    let mut bytes = dell_u2415();
+    assert_eq!(Edid::parse(&bytes[..100]), Err(EdidError::Truncated(100)));
+    bytes[20] += 1;
+    assert_eq!(Edid::parse(&bytes), Err(EdidError::Checksum(0x01)));
+    bytes[0] = 0xFF;
+    assert_eq!(Edid::parse(&bytes), Err(EdidError::Header));
+}
+
+#[test]
+fn friendly_name_falls_back_to_edid() {
+    let backend = MockBackend::new()
+        .with_monitor(MockMonitor::new("MOCK#A").edid(dell_u2415()).feature(
+            Feature::Luminance,
+            Reply {
+                current: 1,
+                maximum: 10,
+                source: Interface::DDCCI,
+            },
+        ))
+        .with_monitor(MockMonitor::new("MOCK#B").name("B").edid(dell_u2415()));
+    let monitors = backend.enumerate();
+    assert_eq!(monitors[0].get_edid().unwrap().product_code, 0xA0BA);
+    assert_eq!(
+        monitors[0].get_user_friendly_name().unwrap().unwrap(),
+        "DELL U2415"
+    );
+    assert_eq!(monitors[1].get_user_friendly_name().unwrap().unwrap(), "B");
+}
+
+#[test]
+fn backlight_reads_panel_edid() {
+    let root = &std::env::temp_dir().join(format!("monitor-edid-{}", std::process::id()));
+    let _ = fs::remove_dir_all(root);
+    let backlight = root.join("class/backlight/intel_backlight");
+    fs::create_dir_all(&backlight).unwrap();
+    fs::write(backlight.join("max_brightness"), "100\n").unwrap();
+    fs::write(backlight.join("brightness"), "40\n").unwrap();
+    for (connector, status) in [("card0-DP-1", "connected"), ("card0-eDP-1", "connected")] {
+        let connector = root.join("class/drm").join(connector);
+        fs::create_dir_all(&connector).unwrap();
+        fs::write(connector.join("status"), format!("{status}\n")).unwrap();
+    }
+    fs::write(root.join("class/drm/card0-eDP-1/edid"), dell_u2415()).unwrap();
+
+    let monitors = BacklightBackend::new(root).enumerate();
+    assert_eq!(
+        monitors[0].get_user_friendly_name().unwrap().unwrap(),
+        "DELL U2415"
+    );
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn encode_round_trips() {
+    let edid = Edid::parse(&dell_u2415()).unwrap();
+    let encoded = Edid::parse(&edid.encode()).unwrap();
+    assert_eq!(
+        encoded,
+        Edid {
+            extensions: 0,
+            ..edid
+        }
+    );
+    assert_eq!(Edid::parse(&Edid::default().encode()).unwrap().year, 1990);
+}
@@ -0,0 +1,67 @@
+use std::io;
+
+use monitor::ddc::{self, DecodeError};
+use monitor::edid::EdidError;
+use monitor::{Error, ErrorKind};
+
+#[test]
+fn io_errors() {
+    let kind = |e: io::Error| Error::from(e).kind();
+    assert_eq!(
+        kind(io::ErrorKind::Unsupported.into()),
+        ErrorKind::NotSupported
+    );
+    assert_eq!(
+        kind(io::ErrorKind::UnexpectedEof.into()),
+        ErrorKind::NoReply
+    );
+    assert_eq!(kind(io::ErrorKind::TimedOut.into()), ErrorKind::Timeout);
+    assert_eq!(
+        kind(io::ErrorKind::PermissionDenied.into()),
+        ErrorKind::Permission
+    );
+    assert_eq!(kind(io::ErrorKind::NotFound.into()), ErrorKind::Io);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn unacknowledged_i2c_transfers() {
+    let kind = |code| Error::from(io::Error::from_raw_os_error(code)).kind();
+    assert_eq!(kind(libc::ENXIO), ErrorKind::NoReply);
+    assert_eq!(kind(libc::EREMOTEIO), ErrorKind::NoReply);
+    assert_eq!(kind(libc::EACCES), ErrorKind::Permission);
+    assert_eq!(kind(libc::EBUSY), ErrorKind::Io);
+}
+
+#[test]
+fn decode_errors() {
+    let mut reply = ddc::encode_response(&ddc::Response::Null);
+    *reply.last_mut().unwrap() ^= 0xFF;
+    let err = Error::from(ddc::decode(&reply).unwrap_err());
+    assert_eq!(err.kind(), ErrorKind::Checksum);
+    // nothing on the bus
+    let err = Error::from(ddc::decode(&[0xFF; 11]).unwrap_err());
+    assert_eq!(err.kind(), ErrorKind::NoReply);
+    assert_eq!(Error::from(DecodeError::Opcode(0x42)).kind(), ErrorKind::Io);
+    assert_eq!(
+        Error::from(EdidError::Checksum(1)).kind(),
+        ErrorKind::Checksum
+    );
+}
+
+#[test]
+fn codes_and_messages() {
+    assert_eq!(ErrorKind::NoSuchMonitor.code(), "noSuchMonitor");
+    assert_eq!(ErrorKind::NotSupported.code(), "notSupported");
+    assert!(ErrorKind::NoReply.is_transient());
+    assert!(!ErrorKind::NotSupported.is_transient());
+    // e.g. an unexpected reply, which comes again
+    assert!(!ErrorKind::Io.is_transient());
+    assert_eq!(Error::from(ErrorKind::Timeout).to_string(), "timed out");
+    let err = Error::new(ErrorKind::NoReply, "monitor replied a null message");
+    assert_eq!(
+        err.to_string(),
+        "the monitor did not reply: monitor replied a null message"
+    );
+    assert!(err.get_ref().is_some());
+}
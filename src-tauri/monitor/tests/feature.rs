@@ -0,0 +1,28 @@
+use monitor::Feature;
+
+#[test]
+fn parse_names_aliases_and_codes() {
+    assert_eq!("luminance".parse(), Ok(Feature::Luminance));
+    assert_eq!("Power-State".parse(), Ok(Feature::PowerState));
+    assert_eq!("power_mode".parse(), Ok(Feature::PowerState));
+    assert_eq!("audio volume".parse(), Ok(Feature::Volume));
+    assert_eq!("0x60".parse(), Ok(Feature::Vcp(0x60)));
+    assert_eq!("0X12".parse(), Ok(Feature::Contrast));
+    assert!("0x100".parse::<Feature>().is_err());
+    assert!("sharpness!".parse::<Feature>().is_err());
+}
+
+#[test]
+fn codes_compare_with_names() {
+    assert_eq!(Feature::Vcp(0x10), Feature::Luminance);
+    assert!(matches!(
+        Feature::Vcp(0xD6).normalize(),
+        Feature::PowerState
+    ));
+    assert_eq!(Feature::Vcp(0x62).to_string(), "volume");
+    assert_eq!(Feature::Vcp(0x60).to_string(), "input");
+    assert_eq!(Feature::Vcp(0x87).to_string(), "0x87");
+    for feature in Feature::NAMED {
+        assert_eq!(feature.to_string().parse(), Ok(feature));
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use monitor::ddc::{self, Response};
+use monitor::i2c::DdcDevice;
+use monitor::pacing::PacingPolicy;
+use monitor::{ErrorKind, Feature, Interface, Monitor, Reply};
+
+/// A display answering DDC/CI on the other end of the bus.
+#[derive(Debug, Default, Clone)]
+struct SimulatedDisplay(Arc<Mutex<DisplayState>>);
+
+#[derive(Debug, Default)]
+struct DisplayState {
+    vcp: BTreeMap<u8, (u16, u16)>,
+    capabilities: Vec<u8>,
+    pending: Vec<u8>,
+    corrupt_replies: bool,
+    /// Corrupts only this many of the next replies.
+    corrupt_next: usize,
+    null_replies: bool,
+}
+
+fn xor(seed: u8, bytes: &[u8]) -> u8 {
+    bytes.iter().fold(seed, |acc, byte| acc ^ byte)
+}
+
+impl Write for SimulatedDisplay {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.0.lock().unwrap();
+        assert_eq!(buf[0], 0x51, "source address");
+        let len = (buf[1] & 0x7F) as usize;
+        assert_eq!(buf.len(), len + 3);
+        assert_eq!(xor(0x6E, &buf[..len + 2]), buf[len + 2], "request checksum");
+        let payload = &buf[2..len + 2];
+        match payload[0] {
+            0x01 => {
+                let code = payload[1];
+                let mut reply = if state.null_replies {
+                    vec![0x6E, 0x80]
+                } else {
+                    let (result, (max, cur)) = match state.vcp.get(&code) {
+                        Some(&value) => (0, value),
+                        None => (1, (0, 0)),
+                    };
+                    let [max_hi, max_lo] = max.to_be_bytes();
+                    let [cur_hi, cur_lo] = cur.to_be_bytes();
+                    vec![
+                        0x6E, 0x88, 0x02, result, code, 0x00, max_hi, max_lo, cur_hi, cur_lo,
+                    ]
+                };
+                reply.push(xor(0x50, &reply));
+                if state.corrupt_replies || state.corrupt_next > 0 {
+                    state.corrupt_next = state.corrupt_next.saturating_sub(1);
+                    *reply.last_mut().unwrap() ^= 0xFF;
+                }
+                reply.resize(11, 0);
+                state.pending = reply;
+            }
+            0x03 => {
+                let value = u16::from_be_bytes([payload[2], payload[3]]);
+                if let Some(entry) = state.vcp.get_mut(&payload[1]) {
+                    entry.1 = value.min(entry.0);
+                }
+            }
+            0xF3 => {
+                let offset = u16::from_be_bytes([payload[1], payload[2]]);
+                let start = (offset as usize).min(state.capabilities.len());
+                let end = (start + 32).min(state.capabilities.len());
+                let mut reply = ddc::encode_response(&Response::Capabilities {
+                    offset,
+                    data: state.capabilities[start..end].to_vec(),
+                });
+                reply.resize(38, 0);
+                state.pending = reply;
+            }
+            opcode => panic!("unexpected opcode {opcode:#04x}"),
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for SimulatedDisplay {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.0.lock().unwrap();
+        let len = buf.len().min(state.pending.len());
+        buf[..len].copy_from_slice(&state.pending[..len]);
+        state.pending.drain(..len);
+        Ok(len)
+    }
+}
+
+fn simulated_monitor() -> (SimulatedDisplay, Monitor) {
+    let display = SimulatedDisplay::default();
+    display.0.lock().unwrap().vcp.insert(0x10, (100, 42));
+    let monitor = Monitor::new(
+        "card0-DP-1".into(),
+        Box::new(DdcDevice::new(display.clone()).with_pacing(PacingPolicy {
+            initial_delay: Duration::from_millis(1),
+            min_delay: Duration::ZERO,
+            max_delay: Duration::from_millis(4),
+            probe_after: 8,
+            retries: 2,
+        })),
+    );
+    (display, monitor)
+}
+
+#[test]
+fn get_and_set_vcp() {
+    let (display, monitor) = simulated_monitor();
+    assert_eq!(
+        monitor.get_feature(Feature::Luminance).unwrap(),
+        Reply {
+            current: 42,
+            maximum: 100,
+            source: Interface::DDCCI,
+        }
+    );
+    monitor.set_feature(Feature::Luminance, 70).unwrap();
+    assert_eq!(display.0.lock().unwrap().vcp[&0x10], (100, 70));
+    assert_eq!(monitor.get_feature(Feature::Luminance).unwrap().current, 70);
+}
+
+#[test]
+fn fragmented_capabilities() {
+    let (display, monitor) = simulated_monitor();
+    let caps = "(prot(monitor)type(LCD)model(SIM)cmds(01 02 03 0C E3 F3)vcp(02 10 12 60(0F 11 12) D6(01 04 05))mccs_ver(2.2))";
+    display.0.lock().unwrap().capabilities = caps.as_bytes().to_vec();
+    let caps = monitor.get_capabilities().unwrap();
+    assert_eq!(caps.model.as_deref(), Some("SIM"));
+    assert_eq!(caps.values(0x60), [0x0F, 0x11, 0x12]);
+    assert_eq!(caps.values(0xD6), [0x01, 0x04, 0x05]);
+}
+
+#[test]
+fn unsupported_vcp() {
+    let (_, monitor) = simulated_monitor();
+    let err = monitor.get_feature(Feature::Volume).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotSupported);
+}
+
+#[test]
+fn bad_replies() {
+    let (display, monitor) = simulated_monitor();
+    display.0.lock().unwrap().corrupt_replies = true;
+    let err = monitor.get_feature(Feature::Luminance).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Checksum);
+
+    let mut state = display.0.lock().unwrap();
+    state.corrupt_replies = false;
+    state.null_replies = true;
+    drop(state);
+    assert!(monitor.get_feature(Feature::Luminance).is_err());
+}
+
+#[test]
+fn retries_corrupt_replies() {
+    let (display, monitor) = simulated_monitor();
+    display.0.lock().unwrap().corrupt_next = 2;
+    assert_eq!(monitor.get_feature(Feature::Luminance).unwrap().current, 42);
+    let stats = monitor.pacing_stats();
+    assert_eq!((stats.failures, stats.retries, stats.given_up), (2, 2, 0));
+
+    display.0.lock().unwrap().corrupt_replies = true;
+    assert!(monitor.get_feature(Feature::Luminance).is_err());
+    assert_eq!(monitor.pacing_stats().given_up, 1);
+}
@@ -0,0 +1,59 @@
+use monitor::edid::Edid;
+use monitor::identity::MonitorId;
+use monitor::mock::{MockBackend, MockMonitor};
+
+fn edid(serial_number: u32, serial: Option<&str>) -> Edid {
+    Edid {
+        manufacturer: "DEL".to_owned(),
+        product_code: 0xA0BA,
+        serial_number,
+        serial: serial.map(str::to_owned),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn derived_from_edid() {
+    assert_eq!(
+        MonitorId::from_edid(&edid(0x3042_4C4C, Some("CFV9N7BJ0LLB"))).as_str(),
+        "DEL-A0BA-30424C4C"
+    );
+    assert_eq!(
+        MonitorId::from_edid(&edid(0, Some("CFV9 N7BJ-0LLB"))).as_str(),
+        "DEL-A0BA-CFV9N7BJ0LLB"
+    );
+    assert_eq!(MonitorId::from_edid(&edid(0, None)).as_str(), "DEL-A0BA");
+}
+
+#[test]
+fn identical_monitors_ordered_by_path() {
+    let backend = MockBackend::new()
+        .with_monitor(MockMonitor::new("PATH#C").edid(edid(0, None).encode()))
+        .with_monitor(MockMonitor::new("PATH#A").edid(edid(0, None).encode()))
+        .with_monitor(MockMonitor::new("PATH#B").edid(edid(7, None).encode()))
+        .with_monitor(MockMonitor::new("PATH#D"));
+    let monitors = monitor::enumerate(&backend);
+    let ids: Vec<_> = monitors
+        .iter()
+        .map(|monitor| monitor.identity.as_str())
+        .collect();
+    assert_eq!(
+        ids,
+        ["DEL-A0BA#2", "DEL-A0BA", "DEL-A0BA-00000007", "PATH#D"]
+    );
+    assert!(monitors[2].matches("DEL-A0BA-00000007"));
+    assert!(monitors[2].matches("PATH#B"));
+    assert!(!monitors[2].matches("DEL-A0BA"));
+}
+
+#[test]
+fn survives_port_changes() {
+    let before =
+        MockBackend::new().with_monitor(MockMonitor::new("PORT#1").edid(edid(1, None).encode()));
+    let after =
+        MockBackend::new().with_monitor(MockMonitor::new("PORT#2").edid(edid(1, None).encode()));
+    assert_eq!(
+        monitor::enumerate(&before)[0].identity,
+        monitor::enumerate(&after)[0].identity
+    );
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use std::time::Duration;
+
+use common::reply;
+use monitor::capabilities::Capabilities;
+use monitor::input::{self, Input, SwitchTiming};
+use monitor::mock::{MockBackend, MockMonitor};
+use monitor::{ErrorKind, Feature, MonitorBackend};
+
+const TIMING: SwitchTiming = SwitchTiming {
+    settle: Duration::from_millis(1),
+    poll: Duration::from_millis(1),
+    timeout: Duration::from_millis(200),
+};
+
+#[test]
+fn names_from_capabilities() {
+    let caps = Capabilities::parse("(vcp(10 60(0F 10 11 E1)))");
+    assert_eq!(
+        input::inputs(&caps),
+        [
+            Input::new(0x0F),
+            Input::new(0x10),
+            Input::new(0x11),
+            Input::new(0xE1)
+        ]
+    );
+    assert_eq!(Input::new(0x0F).name, "DisplayPort-1");
+    assert_eq!(Input::new(0xE1).name, "0xe1");
+
+    assert_eq!(input::parse("hdmi1", &caps), Some(0x11));
+    assert_eq!(input::parse("HDMI", &caps), Some(0x11));
+    assert_eq!(input::parse("dp-2", &caps), Some(0x10));
+    assert_eq!(input::parse("dp", &caps), None);
+    assert_eq!(input::parse("0xE1", &caps), Some(0xE1));
+    assert_eq!(input::parse("17", &caps), Some(0x11));
+    assert_eq!(input::parse("vga1", &caps), None);
+    assert_eq!(input::parse("vga1", &Capabilities::default()), Some(0x01));
+    assert_eq!(input::parse("usb-c", &Capabilities::default()), None);
+    assert_eq!(
+        input::parse("type-c-2", &Capabilities::default()),
+        Some(0x14)
+    );
+}
+
+#[test]
+fn switch_waits_for_monitor_to_answer() {
+    let handle = MockMonitor::new("MOCK#A")
+        .feature(Feature::InputSource, reply(0x0F, 0x12))
+        .silent_after_write(5);
+    let monitor = &MockBackend::new().with_monitor(handle.clone()).enumerate()[0];
+    assert_eq!(
+        input::switch(monitor, 0x11, &TIMING).unwrap(),
+        Some(reply(0x11, 0x12))
+    );
+
+    let handle = handle.silent_after_write(usize::MAX);
+    assert_eq!(input::switch(monitor, 0x0F, &TIMING).unwrap(), None);
+    assert_eq!(handle.value(Feature::InputSource), Some(reply(0x0F, 0x12)));
+}
+
+#[test]
+fn switch_reports_failed_writes() {
+    let monitor = &MockBackend::new()
+        .with_monitor(
+            MockMonitor::new("MOCK#A")
+                .feature(Feature::InputSource, reply(0x0F, 0x12))
+                .fail(Feature::InputSource, ErrorKind::Timeout, 1),
+        )
+        .enumerate()[0];
+    let err = input::switch(monitor, 0x11, &TIMING).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Timeout);
+
+    let monitor = &MockBackend::new()
+        .with_monitor(MockMonitor::new("MOCK#B"))
+        .enumerate()[0];
+    let err = input::switch(monitor, 0x11, &SwitchTiming::default()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotSupported);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::reply;
+use monitor::mock::{MockBackend, MockMonitor};
+use monitor::{ErrorKind, Feature, MonitorBackend};
+
+#[test]
+fn scripted_values_and_failures() {
+    let handle = MockMonitor::new("MOCK#A")
+        .name("A")
+        .feature(Feature::Luminance, reply(10, 100))
+        .fail(Feature::Luminance, ErrorKind::Timeout, 1);
+    let backend = MockBackend::new().with_monitor(handle.clone());
+    let monitors = backend.enumerate();
+    assert_eq!(monitors.len(), 1);
+    let monitor = &monitors[0];
+    assert_eq!(monitor.id, "MOCK#A");
+    assert_eq!(monitor.get_user_friendly_name().unwrap().unwrap(), "A");
+
+    let err = monitor.get_feature(Feature::Luminance).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Timeout);
+    assert_eq!(
+        monitor.get_feature(Feature::Luminance).unwrap(),
+        reply(10, 100)
+    );
+    let err = monitor.get_feature(Feature::Contrast).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotSupported);
+
+    monitor.set_feature(Feature::Luminance, 150).unwrap();
+    assert_eq!(handle.value(Feature::Luminance), Some(reply(100, 100)));
+}
+
+#[test]
+fn state_survives_enumeration() {
+    let backend = MockBackend::new()
+        .with_monitor(MockMonitor::new("MOCK#A").feature(Feature::Volume, reply(1, 10)));
+    backend.enumerate()[0]
+        .set_feature(Feature::Volume, 7)
+        .unwrap();
+    assert_eq!(
+        backend.enumerate()[0].get_feature(Feature::Volume).unwrap(),
+        reply(7, 10)
+    );
+}
+
+#[test]
+fn capabilities() {
+    let backend = MockBackend::new()
+        .with_monitor(
+            MockMonitor::new("MOCK#A")
+                .feature(Feature::Luminance, reply(1, 10))
+                .feature(Feature::Volume, reply(1, 10)),
+        )
+        .with_monitor(MockMonitor::new("MOCK#B").capabilities("(vcp(10 60(0F 11)))"));
+    let monitors = backend.enumerate();
+    let caps = monitors[0].get_capabilities().unwrap();
+    assert_eq!(caps.vcp.keys().copied().collect::<Vec<_>>(), [0x10, 0x62]);
+    let caps = monitors[1].get_capabilities().unwrap();
+    assert_eq!(caps.values(0x60), [0x0F, 0x11]);
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{enumerate, reply};
+use monitor::mock::MockMonitor;
+use monitor::pacing::{PacingPolicy, PacingStats};
+use monitor::{ErrorKind, Feature};
+
+fn policy() -> PacingPolicy {
+    PacingPolicy {
+        initial_delay: Duration::from_millis(8),
+        min_delay: Duration::from_millis(2),
+        max_delay: Duration::from_millis(40),
+        probe_after: 4,
+        retries: 2,
+    }
+}
+
+#[test]
+fn retries_transient_failures() {
+    let monitor = enumerate(
+        MockMonitor::new("MOCK#A")
+            .pacing(policy())
+            .feature(Feature::Luminance, reply(10, 100))
+            .fail(Feature::Luminance, ErrorKind::Checksum, 1)
+            .fail(Feature::Luminance, ErrorKind::Timeout, 1),
+    );
+    assert_eq!(
+        monitor.get_feature(Feature::Luminance).unwrap(),
+        reply(10, 100)
+    );
+    let stats = monitor.pacing_stats();
+    assert_eq!((stats.failures, stats.retries, stats.given_up), (2, 2, 0));
+    assert_eq!(stats.commands, 1);
+    // backing off doubles the delay and raises the floor above the delay that failed
+    assert_eq!(stats.delay, Duration::from_millis(32));
+    assert_eq!(stats.floor, Duration::from_millis(20));
+}
+
+#[test]
+fn gives_up_after_retries() {
+    let monitor = enumerate(
+        MockMonitor::new("MOCK#A")
+            .pacing(policy())
+            .feature(Feature::Luminance, reply(10, 100))
+            .fail(Feature::Luminance, ErrorKind::Timeout, 5),
+    );
+    let err = monitor.set_feature(Feature::Luminance, 20).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Timeout);
+    let stats = monitor.pacing_stats();
+    assert_eq!((stats.failures, stats.retries, stats.given_up), (3, 2, 1));
+    assert_eq!(stats.delay, policy().max_delay);
+}
+
+#[test]
+fn definite_errors_are_not_retried() {
+    let monitor = enumerate(MockMonitor::new("MOCK#A").pacing(policy()));
+    let err = monitor.get_feature(Feature::Contrast).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotSupported);
+    assert_eq!(
+        monitor.pacing_stats(),
+        PacingStats {
+            delay: policy().initial_delay,
+            floor: policy().min_delay,
+            commands: 1,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn learns_shorter_delays() {
+    let monitor = enumerate(
+        MockMonitor::new("MOCK#A")
+            .pacing(policy())
+            .feature(Feature::Luminance, reply(10, 100)),
+    );
+    for _ in 0..4 {
+        monitor.get_feature(Feature::Luminance).unwrap();
+    }
+    assert_eq!(monitor.pacing_stats().delay, Duration::from_millis(6));
+    for _ in 0..32 {
+        monitor.get_feature(Feature::Luminance).unwrap();
+    }
+    let stats = monitor.pacing_stats();
+    assert_eq!(stats.delay, policy().min_delay);
+    assert_eq!(stats.floor, policy().min_delay);
+}
+
+#[test]
+fn spaces_commands() {
+    let monitor = enumerate(
+        MockMonitor::new("MOCK#A")
+            .pacing(policy())
+            .feature(Feature::Luminance, reply(10, 100)),
+    );
+    let start = Instant::now();
+    for _ in 0..3 {
+        monitor.get_feature(Feature::Luminance).unwrap();
+    }
+    assert!(start.elapsed() >= 2 * policy().initial_delay);
+}
+
+#[test]
+fn unpaced_by_default() {
+    let monitor = enumerate(
+        MockMonitor::new("MOCK#A")
+            .feature(Feature::Luminance, reply(10, 100))
+            .fail(Feature::Luminance, ErrorKind::Timeout, 1),
+    );
+    assert!(monitor.get_feature(Feature::Luminance).is_err());
+    assert_eq!(monitor.pacing_stats().given_up, 1);
+}
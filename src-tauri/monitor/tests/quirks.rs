@@ -0,0 +1,125 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{enumerate, reply};
+use monitor::edid::Edid;
+use monitor::mock::{MockBackend, MockMonitor};
+use monitor::quirks::{PacingOverride, QuirksDb};
+use monitor::{ErrorKind, Feature};
+
+fn edid(manufacturer: &str, product_code: u16) -> Edid {
+    Edid {
+        manufacturer: manufacturer.to_owned(),
+        product_code,
+        ..Default::default()
+    }
+}
+
+const TABLE: &str = r#"
+[[monitor]]
+manufacturer = "DEL"
+disabled = ["0x13"]
+pacing = { initial_delay_ms = 300, retries = 5 }
+
+[[monitor]]
+manufacturer = "DEL"
+product = 0xA0BA
+model = "U2415"
+maximum = { volume = 100 }
+remap = { powerstate = { "4" = 5 } }
+power_off = 5
+pacing = { initial_delay_ms = 100 }
+"#;
+
+#[test]
+fn lookup() {
+    let db = QuirksDb::parse(TABLE).unwrap();
+    let quirks = db.lookup(&edid("DEL", 0xA0BA));
+    assert_eq!(quirks.disabled.iter().copied().collect::<Vec<_>>(), [0x13]);
+    assert_eq!(quirks.maximum[&0x62], 100);
+    assert_eq!(quirks.remap[&0xD6][&4], 5);
+    assert_eq!(quirks.power_off, Some(5));
+    // later entries override the fields they set
+    assert_eq!(
+        quirks.pacing,
+        PacingOverride {
+            initial_delay_ms: Some(100),
+            retries: Some(5),
+            ..Default::default()
+        }
+    );
+
+    let quirks = db.lookup(&edid("DEL", 0x4000));
+    assert_eq!(quirks.power_off, None);
+    assert!(quirks.maximum.is_empty());
+    assert!(db.lookup(&edid("GSM", 0xA0BA)).is_empty());
+
+    // a user file re-enabling the feature
+    let user = QuirksDb::parse("[[monitor]]\nmanufacturer = \"DEL\"\ndisabled = []").unwrap();
+    let quirks = db.extend(user).lookup(&edid("DEL", 0xA0BA));
+    assert!(quirks.disabled.is_empty());
+    assert_eq!(quirks.power_off, Some(5));
+}
+
+#[test]
+fn applied_quirks() {
+    let mock = MockMonitor::new("MOCK#A")
+        .capabilities("(vcp(10 13 62 D6(01 04 05)))")
+        .feature(Feature::Luminance, reply(50, 100))
+        .feature(Feature::from(0x13), reply(50, 100))
+        .feature(Feature::Volume, reply(30, 0))
+        .feature(Feature::PowerState, reply(1, 5));
+    let mut monitor = enumerate(mock.clone());
+    monitor.set_quirks(QuirksDb::parse(TABLE).unwrap().lookup(&edid("DEL", 0xA0BA)));
+    assert_eq!(monitor.pacing_stats().delay, Duration::from_millis(100));
+
+    assert_eq!(
+        monitor.get_feature(Feature::from(0x13)).unwrap_err().kind(),
+        ErrorKind::NotSupported
+    );
+    assert!(!monitor.get_capabilities().unwrap().vcp.contains_key(&0x13));
+    assert_eq!(
+        monitor.get_feature(Feature::Volume).unwrap(),
+        reply(30, 100)
+    );
+    monitor.set_feature(Feature::PowerState, 4).unwrap();
+    assert_eq!(mock.value(Feature::PowerState).unwrap().current, 5);
+    assert_eq!(monitor.get_feature(Feature::PowerState).unwrap().current, 4);
+    assert_eq!(monitor.power_off_value(), Some(5));
+}
+
+#[test]
+fn builtin_quirks() {
+    // the built-in table describes real monitors only
+    assert!(QuirksDb::builtin().lookup(&edid("MCK", 0x0001)).is_empty());
+    let dell = QuirksDb::builtin().lookup(&edid("DEL", 0xA0F0));
+    assert!(dell.disabled.contains(&0x13));
+    assert_eq!(dell.power_off, Some(4));
+    let samsung = QuirksDb::builtin().lookup(&edid("SAM", 0x0E0F));
+    assert_eq!(samsung.maximum[&0x62], 100);
+
+    let user = QuirksDb::parse(include_str!("fixtures/quirks.toml")).unwrap();
+    let db = QuirksDb::builtin().extend(user);
+    let monitors = monitor::enumerate(&MockBackend::demo());
+    let external = monitors
+        .iter()
+        .find(|monitor| monitor.matches("MOCK#EXTERNAL#1"))
+        .unwrap();
+    let quirks = db.lookup(&external.get_edid().unwrap());
+    assert_eq!(quirks.power_off, Some(5));
+}
+
+#[test]
+fn invalid_tables() {
+    let kind = |table| QuirksDb::parse(table).unwrap_err().kind();
+    assert_eq!(kind("[[monitor]]\nproduct = 1"), ErrorKind::InvalidInput);
+    assert_eq!(
+        kind("[[monitor]]\nmanufacturer = \"DEL\"\ndisabled = [\"brightnesss\"]"),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        kind("[[monitor]]\nmanufacturer = \"DEL\"\nremap = { input = { \"x\" = 1 } }"),
+        ErrorKind::InvalidInput
+    );
+}
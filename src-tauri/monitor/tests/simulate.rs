@@ -0,0 +1,97 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::reply;
+use monitor::{simulate, ConnectorType, ErrorKind, Feature};
+
+const SIMULATION: &str = include_str!("fixtures/simulation.toml");
+
+#[test]
+fn virtual_monitors() {
+    let monitors = monitor::enumerate(&simulate::parse(SIMULATION).unwrap());
+    assert_eq!(monitors.len(), 2);
+    let dell = &monitors[0];
+    assert_eq!(dell.identity.as_str(), "DEL-A0F0-00000007");
+    assert_eq!(dell.connector, Some(ConnectorType::DisplayPort));
+    assert_eq!(dell.bus().as_deref(), Some("dock"));
+    assert_eq!(monitors[1].bus(), None);
+    assert_eq!(
+        dell.get_user_friendly_name().unwrap(),
+        Some("DELL U2720Q".into())
+    );
+    assert_eq!(dell.get_capabilities().unwrap().values(0x60), [0x0F, 0x11]);
+    assert_eq!(dell.get_feature(Feature::Contrast).unwrap(), reply(75, 80));
+    assert_eq!(
+        dell.get_feature(Feature::InputSource).unwrap(),
+        reply(0x0F, 0x11)
+    );
+
+    dell.set_feature(Feature::Luminance, 95).unwrap();
+    assert_eq!(
+        dell.get_feature(Feature::Luminance).unwrap(),
+        reply(90, 100)
+    );
+    dell.set_feature(Feature::Luminance, 0).unwrap();
+    assert_eq!(
+        dell.get_feature(Feature::Luminance).unwrap(),
+        reply(10, 100)
+    );
+}
+
+#[test]
+fn quirks() {
+    let monitors = monitor::enumerate(&simulate::parse(SIMULATION).unwrap());
+    let locked = &monitors[1];
+    let start = Instant::now();
+    locked.set_feature(Feature::Volume, 60).unwrap();
+    assert_eq!(locked.get_feature(Feature::Volume).unwrap(), reply(30, 100));
+    assert!(start.elapsed() >= Duration::from_millis(40));
+}
+
+#[test]
+fn random_failures() {
+    let simulation = r#"
+        [[monitor]]
+        id = "SIM#FLAKY#0"
+        failure_rate = 0.5
+        seed = 42
+        paced = false
+        features = { luminance = 50 }
+    "#;
+    let count = || {
+        let monitors = monitor::enumerate(&simulate::parse(simulation).unwrap());
+        (0..200)
+            .filter(|_| {
+                monitors[0]
+                    .get_feature(Feature::Luminance)
+                    .is_err_and(|e| e.kind() == ErrorKind::NoReply)
+            })
+            .count()
+    };
+    let failures = count();
+    assert!((50..150).contains(&failures), "{failures} failures");
+    // the same seed fails the same way
+    assert_eq!(count(), failures);
+}
+
+#[test]
+fn invalid_simulations() {
+    let kind = |simulation| simulate::parse(simulation).unwrap_err().kind();
+    assert_eq!(
+        kind("[[monitor]]\nname = \"no id\""),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        kind("[[monitor]]\nid = \"A\"\nfeatures = { brightnesss = 1 }"),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        kind("[[monitor]]\nid = \"A\"\nclamp = { luminance = [90, 10] }"),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        kind("[[monitor]]\nid = \"A\"\nfailure_rate = 2.0"),
+        ErrorKind::InvalidInput
+    );
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use common::reply;
+use monitor::mock::{MockBackend, MockMonitor};
+use monitor::trace::{Op, Record, ReplayBackend, TraceBackend};
+use monitor::{ConnectorType, ErrorKind, Feature};
+
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Buffer {
+    fn records(&self) -> Vec<Record> {
+        let buffer = self.0.lock().unwrap();
+        String::from_utf8_lossy(&buffer)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+}
+
+fn backend() -> MockBackend {
+    MockBackend::demo().with_monitor(
+        MockMonitor::new("MOCK#FLAKY#2")
+            .connector(ConnectorType::Hdmi)
+            .feature(Feature::Luminance, reply(40, 100))
+            .fail(Feature::Luminance, ErrorKind::Checksum, 1),
+    )
+}
+
+#[test]
+fn records_commands() {
+    let buffer = Buffer::default();
+    let backend = TraceBackend::new(Box::new(backend()), buffer.clone());
+    let monitors = monitor::enumerate(&backend);
+    let flaky = &monitors[2];
+    assert_eq!(
+        flaky.get_feature(Feature::Luminance).unwrap_err().kind(),
+        ErrorKind::Checksum
+    );
+    flaky.set_feature(Feature::Luminance, 60).unwrap();
+    assert_eq!(
+        flaky.get_feature(Feature::Luminance).unwrap(),
+        reply(60, 100)
+    );
+
+    let records: Vec<_> = buffer
+        .records()
+        .into_iter()
+        .filter(|record| record.monitor == "MOCK#FLAKY#2")
+        .collect();
+    let ops: Vec<_> = records.iter().map(|record| record.op).collect();
+    assert_eq!(
+        ops,
+        [
+            Op::Enumerate,
+            Op::GetEdid,
+            Op::GetFeature,
+            Op::SetFeature,
+            Op::GetFeature
+        ]
+    );
+    assert_eq!(records[0].connector, Some(ConnectorType::Hdmi));
+    assert_eq!(
+        records[1].error.as_ref().unwrap().kind,
+        ErrorKind::NotSupported
+    );
+    assert_eq!(records[2].code, Some(0x10));
+    assert_eq!(records[2].error.as_ref().unwrap().kind, ErrorKind::Checksum);
+    assert_eq!(records[3].value, Some(60));
+    assert_eq!(records[4].reply, Some(reply(60, 100)));
+}
+
+#[test]
+fn replays_recorded_commands() {
+    let buffer = Buffer::default();
+    let backend = TraceBackend::new(Box::new(backend()), buffer.clone());
+    let monitors = monitor::enumerate(&backend);
+    for monitor in &monitors {
+        let _ = monitor.get_feature(Feature::Luminance);
+        let _ = monitor.get_feature(Feature::Luminance);
+        let _ = monitor.get_capabilities();
+        let _ = monitor.get_user_friendly_name();
+    }
+
+    let replay = ReplayBackend::new(buffer.records());
+    let replayed = monitor::enumerate(&replay);
+    assert_eq!(replayed.len(), monitors.len());
+    for (monitor, replayed) in monitors.iter().zip(&replayed) {
+        assert_eq!(replayed.id, monitor.id);
+        assert_eq!(replayed.identity, monitor.identity);
+        assert_eq!(replayed.connector, monitor.connector);
+        assert_eq!(
+            replayed.get_capabilities().ok(),
+            monitor.get_capabilities().ok()
+        );
+        assert_eq!(
+            replayed.get_user_friendly_name().ok(),
+            monitor.get_user_friendly_name().ok()
+        );
+    }
+    let flaky = &replayed[2];
+    assert_eq!(
+        flaky.get_feature(Feature::Luminance).unwrap_err().kind(),
+        ErrorKind::Checksum
+    );
+    // the last reply is served again once the trace runs out
+    assert_eq!(
+        flaky.get_feature(Feature::Luminance).unwrap(),
+        reply(40, 100)
+    );
+    assert_eq!(
+        flaky.get_feature(Feature::Luminance).unwrap(),
+        reply(40, 100)
+    );
+    assert_eq!(
+        flaky
+            .set_feature(Feature::Luminance, 60)
+            .unwrap_err()
+            .kind(),
+        ErrorKind::NotSupported
+    );
+}
+
+#[test]
+fn invalid_traces() {
+    let trace = "\n{\"time\":0,\"monitor\":\"A\",\"op\":\"getName\",\"latency_us\":0}\n{";
+    let err = ReplayBackend::read(trace.as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.to_string().starts_with("line 3:"));
+}
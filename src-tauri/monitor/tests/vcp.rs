@@ -0,0 +1,52 @@
+use monitor::vcp::{self, Access, Group, Kind};
+use monitor::Feature;
+
+#[test]
+fn table_is_sorted() {
+    let mut last = None;
+    for code in 0..=u8::MAX {
+        if let Some(vcp) = vcp::lookup(code) {
+            assert_eq!(vcp.code, code);
+            assert!(last < Some(code));
+            last = Some(code);
+            if vcp.kind != Kind::NonContinuous {
+                assert!(vcp.values.is_empty(), "{vcp}");
+            }
+        }
+    }
+    assert!(vcp::lookup(0xE0).is_none());
+    assert_eq!(vcp::group(0xE0), Some(Group::Manufacturer));
+}
+
+#[test]
+fn describe_codes() {
+    let input = vcp::lookup(0x60).unwrap();
+    assert_eq!(input.name, "Input Source");
+    assert_eq!(input.kind, Kind::NonContinuous);
+    assert_eq!(input.access, Access::ReadWrite);
+    let frequency = vcp::lookup(0xAC).unwrap();
+    assert!(frequency.access.readable() && !frequency.access.writable());
+    assert_eq!(
+        Feature::Luminance.describe().unwrap().kind,
+        Kind::Continuous
+    );
+}
+
+#[test]
+fn value_names() {
+    assert_eq!(vcp::value_name(0x60, 0x0F), Some("DisplayPort-1"));
+    assert_eq!(vcp::value_name(0x60, 0x11), Some("HDMI-1"));
+    assert_eq!(Feature::PowerState.value_name(2), Some("Standby"));
+    assert_eq!(vcp::value_name(0x14, 0x05), Some("6500K"));
+    assert_eq!(vcp::value_name(0x60, 0x0F00), None);
+    assert_eq!(vcp::value_name(0x60, 0x010F), Some("DisplayPort-1"));
+    assert_eq!(Feature::Luminance.value_name(50), None);
+}
+
+#[test]
+fn parse_mccs_names() {
+    assert_eq!("input source".parse(), Ok(Feature::Vcp(0x60)));
+    assert_eq!("Video-Gain-Red".parse(), Ok(Feature::Vcp(0x16)));
+    assert_eq!("sharpness".parse(), Ok(Feature::Vcp(0x87)));
+    assert_eq!("Audio Speaker Volume".parse(), Ok(Feature::Volume));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{enumerate, reply};
+use monitor::mock::MockMonitor;
+use monitor::verify::{self, Outcome, VerifyTiming};
+use monitor::{ErrorKind, Feature};
+
+fn timing() -> VerifyTiming {
+    VerifyTiming {
+        poll: Duration::from_millis(5),
+        timeout: Duration::from_millis(50),
+    }
+}
+
+#[test]
+fn verified_write() {
+    let monitor = enumerate(MockMonitor::new("MOCK#A").feature(Feature::Luminance, reply(10, 100)));
+    let outcome = verify::set(&monitor, Feature::Luminance, 70, &timing()).unwrap();
+    assert_eq!(outcome, Outcome::Verified(reply(70, 100)));
+}
+
+#[test]
+fn clamped_write_is_a_mismatch() {
+    let monitor = enumerate(MockMonitor::new("MOCK#A").feature(Feature::Luminance, reply(10, 100)));
+    let start = Instant::now();
+    let outcome = verify::set(&monitor, Feature::Luminance, 150, &timing()).unwrap();
+    assert_eq!(
+        outcome,
+        Outcome::Mismatch {
+            requested: 150,
+            actual: 100
+        }
+    );
+    // the feature is polled until the deadline
+    assert!(start.elapsed() >= timing().timeout);
+}
+
+#[test]
+fn waits_for_the_monitor_to_answer() {
+    let monitor = enumerate(
+        MockMonitor::new("MOCK#A")
+            .feature(Feature::Luminance, reply(10, 100))
+            .silent_after_write(3),
+    );
+    let outcome = verify::set(&monitor, Feature::Luminance, 70, &timing()).unwrap();
+    assert_eq!(outcome, Outcome::Verified(reply(70, 100)));
+}
+
+#[test]
+fn read_failures() {
+    let monitor = enumerate(
+        MockMonitor::new("MOCK#A")
+            .feature(Feature::Luminance, reply(10, 100))
+            .silent_after_write(1000),
+    );
+    let err = verify::set(&monitor, Feature::Luminance, 70, &timing()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Timeout);
+
+    let monitor = enumerate(
+        MockMonitor::new("MOCK#A")
+            .feature(Feature::Luminance, reply(10, 100))
+            .fail(Feature::Luminance, ErrorKind::NotSupported, 1),
+    );
+    let err = verify::set(&monitor, Feature::Luminance, 70, &timing()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotSupported);
+    // a failed write is not read back
+    assert_eq!(
+        monitor.get_feature(Feature::Luminance).unwrap(),
+        reply(10, 100)
+    );
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::fake_sysfs;
+use monitor::backlight::BacklightBackend;
+use monitor::edid::Edid;
+use monitor::i2c::I2cBackend;
+use monitor::mock::{MockBackend, MockMonitor};
+use monitor::watch::{self, Watcher};
+use monitor::Monitor;
+
+fn set_connector(root: &Path, name: &str, status: &str, edid: &[u8]) {
+    let dir = root.join("class/drm").join(name);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("status"), format!("{status}\n")).unwrap();
+    fs::write(dir.join("edid"), edid).unwrap();
+}
+
+#[test]
+fn drm_connector_changes() {
+    let root = fake_sysfs("drm");
+    set_connector(&root, "card0-DP-1", "disconnected", &[]);
+    set_connector(&root, "card0-eDP-1", "connected", &Edid::default().encode());
+    let backend = I2cBackend::new(&root, root.join("dev"));
+    let mut watcher = Watcher::new(&backend);
+    assert!(!watcher.poll(&backend));
+
+    let edid = Edid {
+        manufacturer: "DEL".to_owned(),
+        ..Default::default()
+    }
+    .encode();
+    set_connector(&root, "card0-DP-1", "connected", &edid);
+    assert!(watcher.poll(&backend));
+    assert!(!watcher.poll(&backend));
+
+    // another monitor on the same connector
+    set_connector(&root, "card0-DP-1", "connected", &Edid::default().encode());
+    assert!(watcher.poll(&backend));
+
+    set_connector(&root, "card1-HDMI-A-1", "disconnected", &[]);
+    assert!(watcher.poll(&backend));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn backlight_devices_appearing() {
+    let root = fake_sysfs("backlight");
+    fs::create_dir_all(root.join("class/backlight")).unwrap();
+    let backend = (BacklightBackend::new(&root), MockBackend::new());
+    let mut watcher = Watcher::new(&backend);
+    fs::create_dir_all(root.join("class/backlight/intel_backlight")).unwrap();
+    assert!(watcher.poll(&backend));
+    assert!(!watcher.poll(&backend));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+fn ids(monitors: &[(Monitor, u32)]) -> Vec<&str> {
+    monitors
+        .iter()
+        .map(|(monitor, _)| monitor.identity.as_str())
+        .collect()
+}
+
+#[test]
+fn merge_keeps_present_monitors() {
+    let a = MockMonitor::new("PORT#1").edid(
+        Edid {
+            serial_number: 1,
+            ..Default::default()
+        }
+        .encode(),
+    );
+    let c = MockMonitor::new("PORT#3");
+    let before = MockBackend::new()
+        .with_monitor(a.clone())
+        .with_monitor(MockMonitor::new("PORT#2"));
+    let mut monitors: Vec<_> = monitor::enumerate(&before).into_iter().zip(1..).collect();
+
+    let after = MockBackend::new().with_monitor(c).with_monitor(a);
+    let mut next = 10;
+    let changes = watch::merge(
+        &mut monitors,
+        monitor::enumerate(&after),
+        |(monitor, _)| monitor,
+        |monitor| {
+            next += 1;
+            (monitor, next)
+        },
+    );
+    assert_eq!(ids(&monitors), ["PORT#3", "AAA-0000-00000001"]);
+    // the entry of the monitor still present keeps its state
+    assert_eq!(monitors[0].1, 11);
+    assert_eq!(monitors[1].1, 1);
+    assert_eq!(changes.added, [0]);
+    assert_eq!(ids(&changes.removed), ["PORT#2"]);
+
+    let changes = watch::merge(
+        &mut monitors,
+        monitor::enumerate(&after),
+        |(monitor, _)| monitor,
+        |monitor| (monitor, 0),
+    );
+    assert!(changes.is_empty());
+}
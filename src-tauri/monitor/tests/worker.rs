@@ -0,0 +1,227 @@
+mod common;
+
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::{enumerate, reply};
+use monitor::mock::MockMonitor;
+use monitor::worker::{Bus, Priority, Worker};
+use monitor::{ErrorKind, Feature, Reply, Result};
+
+/// Holds the worker busy until the returned sender is dropped.
+fn block(worker: &Worker) -> mpsc::Sender<()> {
+    let (gate, wait) = mpsc::channel::<()>();
+    worker.run(Duration::from_secs(10), Priority::Interactive, move |_| {
+        let _ = wait.recv();
+    });
+    gate
+}
+
+#[test]
+fn coalesces_queued_commands() {
+    let worker = Worker::spawn(enumerate(
+        MockMonitor::new("MOCK#A")
+            .feature(Feature::Luminance, reply(10, 100))
+            .feature(Feature::Contrast, reply(20, 100)),
+    ));
+    let gate = block(&worker);
+    let (tx, rx) = mpsc::channel::<(&str, Result<Reply>)>();
+    for value in 1..=10 {
+        let tx = tx.clone();
+        worker.write(Feature::Luminance, value * 5, move |result| {
+            tx.send(("write", result)).unwrap()
+        });
+    }
+    for _ in 0..3 {
+        for (feature, kind) in [
+            (Feature::Luminance, "read"),
+            (Feature::Contrast, "contrast"),
+        ] {
+            let tx = tx.clone();
+            worker.read(feature, move |result| tx.send((kind, result)).unwrap());
+        }
+    }
+    drop((tx, gate));
+
+    let answers: Vec<_> = rx.iter().collect();
+    assert_eq!(answers.len(), 16);
+    for (kind, result) in answers {
+        let expected = if kind == "contrast" { 20 } else { 50 };
+        assert_eq!(result.unwrap(), reply(expected, 100));
+    }
+    // a write, its read back, which answers the reads, and a read of the contrast
+    assert_eq!(worker.monitor().pacing_stats().commands, 3);
+}
+
+#[test]
+fn closures_are_not_coalesced_across() {
+    let worker = Worker::spawn(enumerate(
+        MockMonitor::new("MOCK#A").feature(Feature::Luminance, reply(10, 100)),
+    ));
+    let gate = block(&worker);
+    let (tx, rx) = mpsc::channel();
+    let write = || {
+        let tx = tx.clone();
+        move |result: Result<Reply>| tx.send(result.unwrap().current).unwrap()
+    };
+    worker.write(Feature::Luminance, 30, write());
+    {
+        let tx = tx.clone();
+        worker.run(Worker::TIMEOUT, Priority::Interactive, move |monitor| {
+            let current = monitor.get_feature(Feature::Luminance).unwrap().current;
+            tx.send(current).unwrap();
+        });
+    }
+    worker.write(Feature::Luminance, 40, write());
+    drop((tx, gate));
+
+    assert_eq!(rx.iter().collect::<Vec<_>>(), [30, 30, 40]);
+    assert_eq!(worker.monitor().pacing_stats().commands, 5);
+}
+
+#[test]
+fn background_closures_let_writes_go_first() {
+    let worker = Worker::spawn(enumerate(
+        MockMonitor::new("MOCK#A").feature(Feature::Luminance, reply(10, 100)),
+    ));
+    let gate = block(&worker);
+    let (tx, rx) = mpsc::channel();
+    {
+        let tx = tx.clone();
+        worker.run(Worker::TIMEOUT, Priority::Background, move |monitor| {
+            let current = monitor.get_feature(Feature::Luminance).unwrap().current;
+            tx.send(current).unwrap();
+        });
+    }
+    for value in [30, 40] {
+        let tx = tx.clone();
+        worker.write(Feature::Luminance, value, move |result| {
+            tx.send(result.unwrap().current).unwrap()
+        });
+    }
+    drop((tx, gate));
+
+    assert_eq!(rx.iter().collect::<Vec<_>>(), [40, 40, 40]);
+}
+
+#[test]
+fn errors_reach_every_caller() {
+    let worker = Worker::spawn(enumerate(
+        MockMonitor::new("MOCK#A").feature(Feature::Luminance, reply(10, 100)),
+    ));
+    let gate = block(&worker);
+    let (tx, rx) = mpsc::channel();
+    for _ in 0..2 {
+        let tx = tx.clone();
+        worker.read(Feature::Volume, move |result| {
+            tx.send(result.unwrap_err().kind()).unwrap()
+        });
+    }
+    drop((tx, gate));
+    assert_eq!(
+        rx.iter().collect::<Vec<_>>(),
+        [ErrorKind::NotSupported, ErrorKind::NotSupported]
+    );
+}
+
+#[test]
+fn unresponsive_monitors() {
+    let worker = Worker::with_timeout(
+        enumerate(MockMonitor::new("MOCK#A").feature(Feature::Luminance, reply(10, 100))),
+        Duration::from_millis(50),
+    );
+    let (tx, rx) = mpsc::channel();
+    worker.run(Duration::from_millis(50), Priority::Interactive, |_| {
+        thread::sleep(Duration::from_millis(200))
+    });
+    worker.read(Feature::Luminance, move |result| tx.send(result).unwrap());
+    thread::sleep(Duration::from_millis(100));
+    assert!(worker.is_unresponsive());
+    // the monitor answers again once the command returns
+    assert_eq!(rx.recv().unwrap().unwrap(), reply(10, 100));
+    assert!(!worker.is_unresponsive());
+
+    // waiting for the bus is not a command past its deadline
+    let bus = Arc::new(Bus::new());
+    let monitor = |id| enumerate(MockMonitor::new(id).feature(Feature::Luminance, reply(10, 100)));
+    let busy = Worker::on_bus(monitor("MOCK#B"), bus.clone(), Worker::TIMEOUT);
+    let waiting = Worker::on_bus(monitor("MOCK#C"), bus, Duration::from_millis(50));
+    busy.run(Worker::TIMEOUT, Priority::Interactive, |_| {
+        thread::sleep(Duration::from_millis(200))
+    });
+    thread::sleep(Duration::from_millis(20));
+    let (tx, rx) = mpsc::channel();
+    waiting.read(Feature::Luminance, move |result| tx.send(result).unwrap());
+    thread::sleep(Duration::from_millis(100));
+    assert!(!waiting.is_unresponsive());
+    assert_eq!(rx.recv().unwrap().unwrap(), reply(10, 100));
+}
+
+#[test]
+fn writes_go_before_reads() {
+    let worker = Worker::spawn(enumerate(
+        MockMonitor::new("MOCK#A")
+            .feature(Feature::Luminance, reply(10, 100))
+            .feature(Feature::Contrast, reply(20, 100)),
+    ));
+    let gate = block(&worker);
+    let (tx, rx) = mpsc::channel();
+    {
+        let tx = tx.clone();
+        worker.read(Feature::Contrast, move |_| tx.send("read").unwrap());
+    }
+    worker.write(Feature::Luminance, 30, move |_| tx.send("write").unwrap());
+    drop(gate);
+    assert_eq!(rx.iter().collect::<Vec<_>>(), ["write", "read"]);
+}
+
+#[test]
+fn shared_buses() {
+    let monitor = |id| {
+        MockMonitor::new(id)
+            .feature(Feature::Luminance, reply(10, 100))
+            .latency(Duration::from_millis(100))
+    };
+    let bus = Arc::new(Bus::new());
+    let workers: Vec<_> = ["MOCK#A", "MOCK#B", "MOCK#C"]
+        .into_iter()
+        .map(|id| Worker::on_bus(enumerate(monitor(id)), bus.clone(), Worker::TIMEOUT))
+        .collect();
+
+    // a poll waiting for the bus lets a write on another monitor go first
+    let gate = block(&workers[0]);
+    let (tx, rx) = mpsc::channel();
+    {
+        let tx = tx.clone();
+        workers[1].read(Feature::Luminance, move |_| tx.send("read").unwrap());
+    }
+    thread::sleep(Duration::from_millis(50));
+    workers[2].write(Feature::Luminance, 30, move |_| tx.send("write").unwrap());
+    thread::sleep(Duration::from_millis(50));
+    drop(gate);
+    assert_eq!(rx.iter().collect::<Vec<_>>(), ["write", "read"]);
+
+    // monitors on a bus take turns, while those on buses of their own run at once
+    let elapsed = |workers: &[Worker]| {
+        let start = Instant::now();
+        let (tx, rx) = mpsc::channel();
+        for worker in workers {
+            let tx = tx.clone();
+            worker.read(Feature::Luminance, move |result| tx.send(result).unwrap());
+        }
+        drop(tx);
+        assert!(rx.iter().all(|result| result.is_ok()));
+        start.elapsed()
+    };
+    assert!(elapsed(&workers[..2]) >= Duration::from_millis(200));
+    let own: Vec<_> = ["MOCK#D", "MOCK#E"]
+        .into_iter()
+        .map(|id| Worker::spawn(enumerate(monitor(id))))
+        .collect();
+    assert!(elapsed(&own) < Duration::from_millis(200));
+    assert_eq!(
+        enumerate(monitor("MOCK#F").bus("dock")).bus().as_deref(),
+        Some("dock")
+    );
+}
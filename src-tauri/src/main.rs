@@ -3,6 +3,7 @@
 
 mod colors;
 mod monitors;
+mod power;
 mod process;
 mod tray;
 mod util;
@@ -15,18 +16,36 @@ fn main() {
     process::ensure_windows_version();
     process::ensure_singleton();
     process::init_com().expect("failed to initialize COM");
+    let mut args: Vec<_> = std::env::args_os().collect();
+    // the panic hook shows the error, e.g. a trace file that cannot be opened
+    let backend = monitor::backend_from_args(&mut args)
+        .unwrap_or_else(|e| panic!("failed to set up the monitor backend: {e}"));
+    if let Some(e) = monitor::quirks::user_file_error() {
+        process::warn(&format!("Ignoring the quirks of the user: {e}"));
+    }
 
     tauri::Builder::default()
         .system_tray(SystemTray::new().with_menu(
             SystemTrayMenu::new().add_item(CustomMenuItem::new("quit".to_owned(), "Quit")),
         ))
-        .manage(monitors::Monitors::new())
+        .manage(monitors::Monitors::with_backend(backend))
+        .setup(|app| {
+            monitors::watch(app.handle());
+            monitors::poll(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             monitors::refresh_monitors,
             monitors::get_monitors,
             monitors::get_monitor_user_friendly_name,
+            monitors::get_monitor_capabilities,
             monitors::get_monitor_feature,
             monitors::set_monitor_feature,
+            monitors::set_monitor_input,
+            monitors::get_monitor_pacing,
+            monitors::get_state,
+            monitors::set_poll_interval,
+            monitors::set_cache_ttl,
             colors::get_accent_colors,
             wm::refresh_panel_style,
             wm::get_workarea_corner,
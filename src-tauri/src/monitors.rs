@@ -1,51 +1,557 @@
-use std::ffi::OsStr;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Weak};
+use std::time::Instant;
 
-use monitor::{Feature, Interface, Monitor};
+use monitor::changes::Changes;
+use monitor::input::SwitchTiming;
+use monitor::pacing::PacingStats;
+use monitor::verify::{Outcome, VerifyTiming};
+use monitor::watch::{self, Watcher};
+use monitor::worker::{Bus, Priority, Worker};
+use monitor::{input, ErrorKind, Feature, Interface, Monitor, MonitorBackend, ParseFeatureError};
 use serde::{Deserialize, Serialize};
-use tauri::async_runtime::{Mutex, RwLock};
-use tauri::State;
-use tokio::time::{sleep, sleep_until, Duration, Instant};
+use tauri::async_runtime::RwLock;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::oneshot;
+use tokio::time::{sleep, timeout, Duration};
 
-use crate::util::JSResult;
+use crate::power;
+use crate::util::{JSError, JSResult};
 
 #[derive(Debug)]
-pub struct Monitors(RwLock<Vec<(Monitor, Mutex<Instant>)>>);
+pub struct Monitors {
+    backend: Arc<dyn MonitorBackend>,
+    watcher: Arc<std::sync::Mutex<Watcher>>,
+    scheduler: std::sync::Mutex<Scheduler>,
+    /// The worker owning each monitor, which runs its DDC/CI commands off the async runtime.
+    monitors: RwLock<Vec<Worker>>,
+    /// What [`get_state`] last read of each monitor, by identity.
+    snapshots: std::sync::Mutex<BTreeMap<String, Snapshot>>,
+    poll_interval: std::sync::Mutex<Duration>,
+    /// The values last read of each monitor, by identity.
+    values: std::sync::Mutex<BTreeMap<String, Values>>,
+    cache_ttl: std::sync::Mutex<Duration>,
+}
+
+/// The values read of a monitor, and how often they were invalidated, so that a read sent before
+/// a write does not put the value overwritten back in the cache.
+#[derive(Debug, Default)]
+struct Values {
+    /// The values by VCP code, with when they were read.
+    replies: BTreeMap<u8, (Instant, monitor::Reply)>,
+    /// Bumped by each invalidation of a feature.
+    generations: BTreeMap<u8, u64>,
+    /// Bumped when every value is invalidated, e.g. on an input switch.
+    epoch: u64,
+}
+
+/// The generation of a feature when a read of it was sent; see [`Monitors::cache`].
+type Generation = (u64, u64);
+
+impl Values {
+    fn generation(&self, feature: Feature) -> Generation {
+        let code = feature.vcp_code();
+        (
+            self.epoch,
+            self.generations.get(&code).copied().unwrap_or(0),
+        )
+    }
+}
+
+/// Serializes the DDC/CI commands per bus, as [`Monitor::bus`] names it: the workers of monitors
+/// sharing one take turns on it, writes before polling reads, while monitors on different buses
+/// are driven in parallel.
+#[derive(Debug, Default)]
+struct Scheduler {
+    buses: BTreeMap<String, Weak<Bus>>,
+}
+
+impl Scheduler {
+    fn spawn(&mut self, monitor: Monitor) -> Worker {
+        // the buses of removed monitors go with their workers
+        self.buses.retain(|_, bus| bus.strong_count() > 0);
+        let bus = match monitor.bus() {
+            Some(name) => {
+                let bus = self
+                    .buses
+                    .get(&name)
+                    .and_then(Weak::upgrade)
+                    .unwrap_or_default();
+                self.buses.insert(name, Arc::downgrade(&bus));
+                bus
+            }
+            None => Arc::default(),
+        };
+        Worker::on_bus(monitor, bus, Worker::TIMEOUT)
+    }
+}
 
 impl Monitors {
-    pub const fn new() -> Monitors {
-        Monitors(RwLock::const_new(Vec::new()))
+    pub fn with_backend(backend: Box<dyn MonitorBackend>) -> Monitors {
+        Monitors {
+            watcher: Arc::new(std::sync::Mutex::new(Watcher::new(&*backend))),
+            backend: backend.into(),
+            scheduler: Default::default(),
+            monitors: RwLock::const_new(Vec::new()),
+            snapshots: Default::default(),
+            poll_interval: std::sync::Mutex::new(POLL_INTERVAL),
+            values: Default::default(),
+            cache_ttl: std::sync::Mutex::new(CACHE_TTL),
+        }
+    }
+
+    /// Returns the value of a feature if it was read within the TTL.
+    fn cached(&self, monitor: &Monitor, feature: Feature) -> Option<monitor::Reply> {
+        let ttl = *self.cache_ttl.lock().unwrap();
+        let values = self.values.lock().unwrap();
+        let (time, reply) = values
+            .get(monitor.identity.as_str())?
+            .replies
+            .get(&feature.vcp_code())?;
+        (time.elapsed() < ttl).then_some(*reply)
+    }
+
+    /// Returns the generation of a feature, taken before sending a command whose value is cached.
+    fn generation(&self, monitor: &Monitor, feature: Feature) -> Generation {
+        let mut values = self.values.lock().unwrap();
+        let values = values.entry(monitor.identity.to_string()).or_default();
+        values.generation(feature)
+    }
+
+    /// Stores the value of a feature just read, unless it was invalidated since `generation` was
+    /// taken or the monitor is gone.
+    fn cache(
+        &self,
+        monitor: &Monitor,
+        feature: Feature,
+        generation: Generation,
+        reply: monitor::Reply,
+    ) {
+        let mut values = self.values.lock().unwrap();
+        let Some(values) = values.get_mut(monitor.identity.as_str()) else {
+            return;
+        };
+        if values.generation(feature) == generation {
+            values
+                .replies
+                .insert(feature.vcp_code(), (Instant::now(), reply));
+        }
+    }
+
+    /// Drops the value of a feature, along with the reads of it still running.
+    fn invalidate(&self, monitor: &Monitor, feature: Feature) {
+        let mut values = self.values.lock().unwrap();
+        let values = values.entry(monitor.identity.to_string()).or_default();
+        values.replies.remove(&feature.vcp_code());
+        *values.generations.entry(feature.vcp_code()).or_default() += 1;
+    }
+
+    /// Drops every value of a monitor, along with the reads still running.
+    fn invalidate_all(&self, monitor: &Monitor) {
+        let mut values = self.values.lock().unwrap();
+        let values = values.entry(monitor.identity.to_string()).or_default();
+        values.replies.clear();
+        values.epoch += 1;
+    }
+
+    /// Reads a feature through the cache, so that callers reading it within the TTL share one
+    /// DDC/CI command; `force` reads it from the monitor anyway. See [`submit`].
+    fn read_cached<'a>(
+        &'a self,
+        worker: &'a Worker,
+        feature: Feature,
+        force: bool,
+    ) -> impl Future<Output = monitor::Result<monitor::Reply>> + 'a {
+        let cached = if force {
+            None
+        } else {
+            self.cached(worker.monitor(), feature)
+        };
+        let pending = cached.is_none().then(|| {
+            let generation = self.generation(worker.monitor(), feature);
+            (generation, read(worker, feature))
+        });
+        async move {
+            match (cached, pending) {
+                (Some(reply), _) => Ok(reply),
+                (None, Some((generation, pending))) => {
+                    let reply = pending.await?;
+                    self.cache(worker.monitor(), feature, generation, reply);
+                    Ok(reply)
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+
+    /// Keeps the snapshot of a monitor in step with the values written to or read from it;
+    /// returns whether the value changed.
+    fn remember(&self, monitor: &Monitor, feature: Feature, reply: Reply) -> bool {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        let Some(snapshot) = snapshots.get_mut(monitor.identity.as_str()) else {
+            return false;
+        };
+        let name = feature.to_string();
+        match snapshot
+            .features
+            .iter_mut()
+            .find(|state| state.name == name)
+        {
+            Some(state) if state.value != reply => {
+                state.value = reply;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Reads the features in the snapshots again and emits `monitor-feature-changed` for those
+    /// that changed. Monitors that report the controls changed on them are only asked for those;
+    /// see [`monitor::changes`].
+    async fn poll(&self, app: &AppHandle) {
+        let monitors = self.monitors.read().await;
+        let snapshots: Vec<(Vec<Feature>, bool)> = {
+            let snapshots = self.snapshots.lock().unwrap();
+            monitors
+                .iter()
+                .map(
+                    |worker| match snapshots.get(worker.monitor().identity.as_str()) {
+                        Some(snapshot) => (
+                            snapshot
+                                .features
+                                .iter()
+                                .filter_map(|state| state.name.parse().ok())
+                                .collect(),
+                            snapshot.polled_in_full,
+                        ),
+                        None => (Vec::new(), true),
+                    },
+                )
+                .collect()
+        };
+        let changes: Vec<_> = monitors
+            .iter()
+            .zip(&snapshots)
+            .map(|(worker, (features, polled_in_full))| {
+                // asked in the background, so that writes queued meanwhile are not held up
+                (!features.is_empty() && !polled_in_full).then(|| {
+                    run(
+                        worker,
+                        worker.timeout(),
+                        Priority::Background,
+                        Monitor::changes,
+                    )
+                })
+            })
+            .collect();
+        let mut polled = Vec::new();
+        for ((worker, (features, _)), changes) in monitors.iter().zip(snapshots).zip(changes) {
+            let features = match changes {
+                Some(changes) => match changes.await {
+                    Ok(Changes::Codes(codes)) => features
+                        .into_iter()
+                        .filter(|feature| codes.contains(&feature.vcp_code()))
+                        .collect(),
+                    Ok(Changes::Unknown) => {
+                        self.poll_in_full(worker.monitor());
+                        features
+                    }
+                    Err(_) => features,
+                },
+                None => features,
+            };
+            polled.push(features);
+        }
+        let replies: Vec<Vec<_>> = monitors
+            .iter()
+            .zip(polled)
+            .map(|(worker, features)| {
+                features
+                    .into_iter()
+                    .map(|feature| (feature, self.read_cached(worker, feature, true)))
+                    .collect()
+            })
+            .collect();
+        for (worker, replies) in monitors.iter().zip(replies) {
+            let monitor = worker.monitor();
+            for (feature, reply) in replies {
+                // a failed read is retried on the next poll
+                let Ok(reply) = reply.await else {
+                    continue;
+                };
+                let reply = Reply::new(feature, reply);
+                if self.remember(monitor, feature, reply) {
+                    let _ = app.emit_all(
+                        "monitor-feature-changed",
+                        FeatureChanged {
+                            id: monitor.identity.to_string(),
+                            feature: feature.to_string(),
+                            value: reply,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    fn poll_in_full(&self, monitor: &Monitor) {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        if let Some(snapshot) = snapshots.get_mut(monitor.identity.as_str()) {
+            snapshot.polled_in_full = true;
+        }
+    }
+
+    /// Re-enumerates the monitors, keeping the workers of those still present, and emits
+    /// `monitor-added` and `monitor-removed` for the others.
+    async fn refresh(&self, app: &AppHandle) {
+        // enumerating reads the EDID of every monitor, which blocks
+        let watcher = Arc::clone(&self.watcher);
+        let backend = Arc::clone(&self.backend);
+        let Ok(fresh) = tauri::async_runtime::spawn_blocking(move || {
+            watcher.lock().unwrap().poll(&*backend);
+            monitor::enumerate(&*backend)
+        })
+        .await
+        else {
+            return;
+        };
+        let mut monitors = self.monitors.write().await;
+        let mut scheduler = self.scheduler.lock().unwrap();
+        let changes = watch::merge(&mut monitors, fresh, Worker::monitor, |monitor| {
+            scheduler.spawn(monitor)
+        });
+        drop(scheduler);
+        for i in changes.added {
+            let _ = app.emit_all("monitor-added", MonitorInfo::new(monitors[i].monitor()));
+        }
+        let mut values = self.values.lock().unwrap();
+        for worker in changes.removed {
+            values.remove(worker.monitor().identity.as_str());
+            let _ = app.emit_all("monitor-removed", MonitorInfo::new(worker.monitor()));
+        }
     }
 }
 
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Refreshes the monitors in the background whenever the backend reports a change, e.g. a DRM
+/// connector changing its status on Linux.
+pub fn watch(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            sleep(WATCH_INTERVAL).await;
+            let monitors = app.state::<Monitors>();
+            // the backend may read sysfs to tell, which blocks
+            let watcher = Arc::clone(&monitors.watcher);
+            let backend = Arc::clone(&monitors.backend);
+            let changed = tauri::async_runtime::spawn_blocking(move || {
+                watcher.lock().unwrap().poll(&*backend)
+            })
+            .await
+            .unwrap_or(false);
+            if changed {
+                monitors.refresh(&app).await;
+            }
+        }
+    });
+}
+
+/// How often [`poll`] reads the features unless told otherwise.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The payload of `monitor-feature-changed`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureChanged {
+    id: String,
+    feature: String,
+    value: Reply,
+}
+
+/// Reads the features of the monitors in the background, so that values changed from the OSD
+/// of a monitor or by other tools reach the panel as `monitor-feature-changed` events. Polling
+/// is paused while the panel is hidden or the computer runs on battery.
+pub fn poll(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let monitors = app.state::<Monitors>();
+            let interval = *monitors.poll_interval.lock().unwrap();
+            sleep(interval).await;
+            let visible = app
+                .get_window("panel")
+                .is_some_and(|panel| panel.is_visible().unwrap_or(false));
+            if visible && !power::on_battery() {
+                monitors.poll(&app).await;
+            }
+        }
+    });
+}
+
+/// How long a value read from a monitor is served to other callers unless told otherwise.
+const CACHE_TTL: Duration = Duration::from_secs(2);
+
 #[tauri::command]
-pub async fn refresh_monitors(monitors: State<'_, Monitors>) -> JSResult<()> {
-    let mut monitors = monitors.0.write().await;
-    monitors.clear();
-    let stub_instant = Instant::now();
-    for monitor in monitor::get_monitors() {
-        monitors.push((monitor, Mutex::const_new(stub_instant)));
-    }
+pub fn set_cache_ttl(monitors: State<'_, Monitors>, ttl_ms: u64) -> JSResult<()> {
+    *monitors.cache_ttl.lock().unwrap() = Duration::from_millis(ttl_ms);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_poll_interval(monitors: State<'_, Monitors>, interval_ms: u64) -> JSResult<()> {
+    *monitors.poll_interval.lock().unwrap() =
+        Duration::from_millis(interval_ms).max(MIN_POLL_INTERVAL);
     Ok(())
 }
 
 #[tauri::command]
-pub async fn get_monitors(monitors: State<'_, Monitors>) -> JSResult<Vec<String>> {
-    let monitors = monitors.0.read().await;
+pub async fn refresh_monitors(app: AppHandle, monitors: State<'_, Monitors>) -> JSResult<()> {
+    monitors.refresh(&app).await;
+    Ok(())
+}
+
+/// A monitor as listed to JS: `id` is the stable identity, `path` the OS path. Commands accept
+/// either of them. `powerOffValue` is the power state that turns the monitor off if its quirks
+/// say so; see [`monitor::quirks`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct MonitorInfo {
+    id: String,
+    path: String,
+    powerOffValue: Option<u32>,
+}
+
+impl MonitorInfo {
+    fn new(monitor: &Monitor) -> MonitorInfo {
+        MonitorInfo {
+            id: monitor.identity.to_string(),
+            path: monitor.id.to_string_lossy().into_owned(),
+            powerOffValue: monitor.power_off_value(),
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_monitors(monitors: State<'_, Monitors>) -> JSResult<Vec<MonitorInfo>> {
+    let monitors = monitors.monitors.read().await;
     Ok(monitors
         .iter()
-        .map(|(monitor, _)| monitor.id.to_string_lossy().into_owned())
+        .map(|worker| MonitorInfo::new(worker.monitor()))
         .collect())
 }
 
-fn get_monitor_by_id<'a>(
-    monitors: &'a [(Monitor, Mutex<Instant>)],
-    id: &'_ str,
-) -> JSResult<&'a (Monitor, Mutex<Instant>)> {
-    let id_os: &OsStr = id.as_ref();
+fn get_monitor_by_id<'a>(monitors: &'a [Worker], id: &'_ str) -> JSResult<&'a Worker> {
     monitors
         .iter()
-        .find(|(monitor, _)| monitor.id == id_os)
-        .ok_or_else(|| format!("no such monitor: '{id}'").into())
+        .find(|worker| worker.monitor().matches(id))
+        .ok_or_else(|| {
+            JSError::new(
+                ErrorKind::NoSuchMonitor.code(),
+                format!("no such monitor: '{id}'"),
+            )
+            .with_monitor(id)
+        })
+}
+
+/// How often a command waiting on a worker checks whether the monitor stopped answering.
+const RESPONSIVE_POLL: Duration = Duration::from_millis(100);
+
+/// How long reading the capabilities string, which takes many commands, may take.
+const CAPABILITIES_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn unresponsive() -> monitor::Error {
+    monitor::Error::new(ErrorKind::Timeout, "the monitor is not responding")
+}
+
+/// Waits for the answer of a worker, giving up once the command it runs is past its deadline.
+async fn answer<T>(
+    worker: &Worker,
+    mut rx: oneshot::Receiver<monitor::Result<T>>,
+) -> monitor::Result<T> {
+    loop {
+        match timeout(RESPONSIVE_POLL, &mut rx).await {
+            Ok(Ok(result)) => return result,
+            Ok(Err(_)) => {
+                return Err(monitor::Error::new(
+                    ErrorKind::Io,
+                    "the worker of the monitor stopped",
+                ))
+            }
+            Err(_) if worker.is_unresponsive() => return Err(unresponsive()),
+            Err(_) => (),
+        }
+    }
+}
+
+/// Queues a command on the worker right away and returns its answer, so that the commands of
+/// several monitors run at once however they are awaited. Commands to a monitor that stopped
+/// answering fail at once rather than queue behind the command it hangs on.
+fn submit<'a, T: 'a>(
+    worker: &'a Worker,
+    send: impl FnOnce(oneshot::Sender<monitor::Result<T>>),
+) -> impl Future<Output = monitor::Result<T>> + 'a {
+    let responsive = !worker.is_unresponsive();
+    let (tx, rx) = oneshot::channel();
+    if responsive {
+        send(tx);
+    }
+    async move {
+        if !responsive {
+            return Err(unresponsive());
+        }
+        answer(worker, rx).await
+    }
+}
+
+/// Reads a feature on the worker; see [`submit`].
+fn read(
+    worker: &Worker,
+    feature: Feature,
+) -> impl Future<Output = monitor::Result<monitor::Reply>> + '_ {
+    submit(worker, move |tx| {
+        worker.read(feature, move |result| {
+            let _ = tx.send(result);
+        })
+    })
+}
+
+/// Writes a feature on the worker and reads it back; see [`submit`].
+fn write(
+    worker: &Worker,
+    feature: Feature,
+    value: u32,
+) -> impl Future<Output = monitor::Result<monitor::Reply>> + '_ {
+    submit(worker, move |tx| {
+        worker.write(feature, value, move |result| {
+            let _ = tx.send(result);
+        })
+    })
+}
+
+/// Runs `f` on the worker, due after `due`; see [`submit`] and [`Worker::run`].
+fn run<T: Send + 'static>(
+    worker: &Worker,
+    due: Duration,
+    priority: Priority,
+    f: impl FnOnce(&Monitor) -> monitor::Result<T> + Send + 'static,
+) -> impl Future<Output = monitor::Result<T>> + '_ {
+    submit(worker, move |tx| {
+        worker.run(due, priority, move |monitor| {
+            let _ = tx.send(f(monitor));
+        })
+    })
+}
+
+/// Tells JS which monitor, and which feature if any, an error is about.
+fn context(id: &str, feature: Option<Feature>) -> impl Fn(monitor::Error) -> JSError + '_ {
+    move |e| {
+        let e = JSError::from(e).with_monitor(id);
+        match feature {
+            Some(feature) => e.with_feature(feature),
+            None => e,
+        }
+    }
 }
 
 #[tauri::command]
@@ -53,23 +559,79 @@ pub async fn get_monitor_user_friendly_name(
     monitors: State<'_, Monitors>,
     id: String,
 ) -> JSResult<Option<String>> {
-    let monitors = monitors.0.read().await;
-    let monitor = &get_monitor_by_id(&monitors, &id)?.0;
-    Ok(monitor
-        .get_user_friendly_name()?
-        .map(|s| s.to_string_lossy().into_owned()))
-}
-
-fn feature_from_string(mut feature_name: String) -> JSResult<Feature> {
-    feature_name.make_ascii_lowercase();
-    Ok(match feature_name.as_str() {
-        "luminance" => Feature::Luminance,
-        "contrast" => Feature::Contrast,
-        "brightness" => Feature::Brightness,
-        "volume" => Feature::Volume,
-        "powerstate" => Feature::PowerState,
-        _ => return Err(format!("invalid feature name: '{feature_name}'").into()),
-    })
+    let monitors = monitors.monitors.read().await;
+    let worker = get_monitor_by_id(&monitors, &id)?;
+    let name = run(
+        worker,
+        worker.timeout(),
+        Priority::Interactive,
+        Monitor::get_user_friendly_name,
+    )
+    .await
+    .map_err(context(&id, None))?;
+    Ok(name.map(|s| s.to_string_lossy().into_owned()))
+}
+
+fn feature_from_string(feature_name: String) -> JSResult<Feature> {
+    feature_name
+        .parse()
+        .map_err(|e: ParseFeatureError| JSError::from(e.to_string()).with_feature(feature_name))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct Capabilities {
+    kind: Option<String>,
+    model: Option<String>,
+    vcp: BTreeMap<u8, Vec<u8>>,
+    mccsVersion: Option<String>,
+    features: Vec<String>,
+    inputs: Vec<Input>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Input {
+    value: u8,
+    name: String,
+}
+
+impl Capabilities {
+    fn new(caps: monitor::capabilities::Capabilities) -> Capabilities {
+        Capabilities {
+            features: Feature::NAMED
+                .iter()
+                .filter(|feature| caps.supports(feature.vcp_code()))
+                .map(ToString::to_string)
+                .collect(),
+            inputs: input::inputs(&caps)
+                .into_iter()
+                .map(|input::Input { value, name }| Input { value, name })
+                .collect(),
+            kind: caps.kind,
+            model: caps.model,
+            vcp: caps.vcp,
+            mccsVersion: caps.mccs_version.map(|version| version.to_string()),
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_monitor_capabilities(
+    monitors: State<'_, Monitors>,
+    id: String,
+) -> JSResult<Capabilities> {
+    let monitors = monitors.monitors.read().await;
+    let worker = get_monitor_by_id(&monitors, &id)?;
+    let caps = run(
+        worker,
+        CAPABILITIES_TIMEOUT,
+        Priority::Interactive,
+        Monitor::get_capabilities,
+    )
+    .await
+    .map_err(context(&id, None))?;
+
+    Ok(Capabilities::new(caps))
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -77,69 +639,397 @@ pub struct Reply {
     current: u32,
     maximum: u32,
     source: &'static str,
+    /// The MCCS name of a non-continuous value, e.g. `DisplayPort-1` or `Standby`.
+    label: Option<&'static str>,
 }
 
-// TODO: move it into JS
-const UPDATE_INTERVAL: Duration = Duration::from_millis(200);
+impl Reply {
+    fn new(feature: Feature, reply: monitor::Reply) -> Reply {
+        let monitor::Reply {
+            current,
+            maximum,
+            source,
+        } = reply;
+        Reply {
+            current,
+            maximum,
+            source: match source {
+                Interface::DDCCI => "ddcci",
+                Interface::IOCTL => "ioctl",
+                Interface::Backlight => "backlight",
+            },
+            label: feature.value_name(current),
+        }
+    }
+}
 
+/// Reads a feature, or returns the value read within the cache TTL unless `force` is set.
 #[tauri::command]
 pub async fn get_monitor_feature(
-    monitors: State<'_, Monitors>,
+    monitors_state: State<'_, Monitors>,
     id: String,
     feature: String,
+    force: Option<bool>,
 ) -> JSResult<Reply> {
-    let monitors = monitors.0.read().await;
-    let (monitor, instant) = get_monitor_by_id(&monitors, &id)?;
-    let feature = feature_from_string(feature)?;
-
-    let mut instant = instant.lock().await;
-    sleep_until(*instant).await;
-    let monitor::Reply {
-        current,
-        maximum,
-        source,
-    } = monitor.get_feature(feature)?;
-    *instant = Instant::now() + UPDATE_INTERVAL;
-
-    Ok(Reply {
-        current,
-        maximum,
-        source: match source {
-            Interface::DDCCI => "ddcci",
-            Interface::IOCTL => "ioctl",
-        },
-    })
+    let monitors = monitors_state.monitors.read().await;
+    let worker = get_monitor_by_id(&monitors, &id)?;
+    let feature = feature_from_string(feature).map_err(|e| e.with_monitor(&id))?;
+    let reply = monitors_state
+        .read_cached(worker, feature, force.unwrap_or(false))
+        .await
+        .map_err(context(&id, Some(feature)))?;
+
+    Ok(Reply::new(feature, reply))
 }
 
+/// Sets a feature and returns the value the monitor reports. Writes queued while the monitor is
+/// busy are collapsed into the latest one. With `verify`, reads it back until it matches and
+/// throws a `mismatch` error if it does not in time.
 #[tauri::command]
 pub async fn set_monitor_feature(
-    monitors: State<'_, Monitors>,
+    monitors_state: State<'_, Monitors>,
     id: String,
     feature: String,
     value: u32,
+    verify: Option<bool>,
 ) -> JSResult<Reply> {
-    let monitors = monitors.0.read().await;
-    let (monitor, instant) = get_monitor_by_id(&monitors, &id)?;
-    let feature = feature_from_string(feature)?;
-
-    let mut instant = instant.lock().await;
-    sleep_until(*instant).await;
-    monitor.set_feature(feature, value)?;
-
-    sleep(UPDATE_INTERVAL).await;
-    let monitor::Reply {
-        current,
-        maximum,
-        source,
-    } = monitor.get_feature(feature)?;
-    *instant = Instant::now() + UPDATE_INTERVAL;
-
-    Ok(Reply {
-        current,
-        maximum,
-        source: match source {
-            Interface::DDCCI => "ddcci",
-            Interface::IOCTL => "ioctl",
-        },
+    let monitors = monitors_state.monitors.read().await;
+    let worker = get_monitor_by_id(&monitors, &id)?;
+    let feature = feature_from_string(feature).map_err(|e| e.with_monitor(&id))?;
+    let monitor = worker.monitor();
+    monitors_state.invalidate(monitor, feature);
+    let generation = monitors_state.generation(monitor, feature);
+    if verify.unwrap_or(false) {
+        let due = worker.timeout() + VerifyTiming::default().timeout;
+        let outcome = run(worker, due, Priority::Interactive, move |monitor| {
+            monitor.set_feature_verified(feature, value)
+        })
+        .await
+        .map_err(context(&id, Some(feature)))?;
+        return match outcome {
+            Outcome::Verified(reply) => {
+                monitors_state.cache(monitor, feature, generation, reply);
+                let reply = Reply::new(feature, reply);
+                monitors_state.remember(monitor, feature, reply);
+                Ok(reply)
+            }
+            Outcome::Mismatch { requested, actual } => Err(JSError::new(
+                "mismatch",
+                format!("requested {requested}, the monitor reports {actual}"),
+            )
+            .with_monitor(&id)
+            .with_feature(feature)),
+        };
+    }
+    let reply = write(worker, feature, value)
+        .await
+        .map_err(context(&id, Some(feature)))?;
+    monitors_state.cache(monitor, feature, generation, reply);
+    let reply = Reply::new(feature, reply);
+    monitors_state.remember(monitor, feature, reply);
+
+    Ok(reply)
+}
+
+/// Switches to an input given by name, e.g. `hdmi1`, or value; returns `None` if the monitor
+/// stopped answering after the switch.
+#[tauri::command]
+pub async fn set_monitor_input(
+    monitors_state: State<'_, Monitors>,
+    id: String,
+    input: String,
+) -> JSResult<Option<Reply>> {
+    let monitors = monitors_state.monitors.read().await;
+    let worker = get_monitor_by_id(&monitors, &id)?;
+    // only names need the capabilities string, which is slow to read
+    let value = match input.parse() {
+        Ok(value) => value,
+        Err(_) => {
+            let caps = run(
+                worker,
+                CAPABILITIES_TIMEOUT,
+                Priority::Interactive,
+                Monitor::get_capabilities,
+            )
+            .await
+            .unwrap_or_default();
+            input::parse(&input, &caps).ok_or_else(|| {
+                JSError::from(format!("invalid input: '{input}'"))
+                    .with_monitor(&id)
+                    .with_feature(Feature::InputSource)
+            })?
+        }
+    };
+    // other features may change with the input
+    monitors_state.invalidate_all(worker.monitor());
+    let due = worker.timeout() + SwitchTiming::default().timeout;
+    let reply = run(worker, due, Priority::Interactive, move |monitor| {
+        monitor.set_input(value)
     })
+    .await
+    .map_err(context(&id, Some(Feature::InputSource)))?;
+    let reply = reply.map(|reply| Reply::new(Feature::InputSource, reply));
+    if let Some(reply) = reply {
+        monitors_state.remember(worker.monitor(), Feature::InputSource, reply);
+    }
+
+    Ok(reply)
+}
+
+/// The delay learned between the DDC/CI commands of a monitor and the failures seen so far.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct Pacing {
+    delayMs: u64,
+    floorMs: u64,
+    commands: u64,
+    failures: u64,
+    retries: u64,
+    givenUp: u64,
+}
+
+#[tauri::command]
+pub async fn get_monitor_pacing(monitors: State<'_, Monitors>, id: String) -> JSResult<Pacing> {
+    let monitors = monitors.monitors.read().await;
+    let monitor = get_monitor_by_id(&monitors, &id)?.monitor();
+    let PacingStats {
+        delay,
+        floor,
+        commands,
+        failures,
+        retries,
+        given_up,
+    } = monitor.pacing_stats();
+    Ok(Pacing {
+        delayMs: delay.as_millis() as u64,
+        floorMs: floor.as_millis() as u64,
+        commands,
+        failures,
+        retries,
+        givenUp: given_up,
+    })
+}
+
+/// The features read of monitors whose capabilities cannot be read.
+const FALLBACK_FEATURES: [Feature; 5] = [
+    Feature::Luminance,
+    Feature::Contrast,
+    Feature::Brightness,
+    Feature::Volume,
+    Feature::PowerState,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureState {
+    name: String,
+    value: Reply,
+}
+
+/// What the panel shows of a monitor; see [`get_state`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MonitorState {
+    #[serde(flatten)]
+    info: MonitorInfo,
+    name: Option<String>,
+    capabilities: Option<Capabilities>,
+    features: Vec<FeatureState>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Snapshot {
+    name: Option<String>,
+    capabilities: Option<Capabilities>,
+    features: Vec<FeatureState>,
+    /// Set once the monitor does not tell which controls changed, so that [`poll`] reads every
+    /// feature of it without asking first.
+    polled_in_full: bool,
+}
+
+impl Snapshot {
+    /// The features to read: those in the capabilities, or a common few if the monitor lists
+    /// none of them, e.g. for want of a capabilities string.
+    fn features(&self) -> Vec<Feature> {
+        let features: Vec<Feature> = self
+            .capabilities
+            .iter()
+            .flat_map(|caps| &caps.features)
+            .filter_map(|name| name.parse().ok())
+            .collect();
+        if features.is_empty() {
+            FALLBACK_FEATURES.to_vec()
+        } else {
+            features
+        }
+    }
+
+    /// Takes the value read of a feature; one that failed keeps the last value unless the
+    /// monitor says it does not support the feature.
+    fn update(&mut self, feature: Feature, result: monitor::Result<monitor::Reply>) {
+        let name = feature.to_string();
+        let idx = self.features.iter().position(|state| state.name == name);
+        match (result, idx) {
+            (Ok(reply), Some(idx)) => self.features[idx].value = Reply::new(feature, reply),
+            (Ok(reply), None) => self.features.push(FeatureState {
+                name,
+                value: Reply::new(feature, reply),
+            }),
+            (Err(e), Some(idx)) if e.kind() == ErrorKind::NotSupported => {
+                self.features.remove(idx);
+            }
+            (Err(_), _) => (),
+        }
+    }
+
+    /// Takes the name, the capabilities and the features in `fresh`, which was read from `self`
+    /// earlier, keeping the values of those not in `read`, which the poller may have updated.
+    fn merge(&mut self, fresh: Snapshot, read: &[Feature]) {
+        let features = fresh
+            .features
+            .into_iter()
+            .map(|state| {
+                let polled = state
+                    .name
+                    .parse()
+                    .is_ok_and(|feature: Feature| !read.contains(&feature));
+                let kept = polled
+                    .then(|| self.features.iter().find(|kept| kept.name == state.name))
+                    .flatten();
+                kept.cloned().unwrap_or(state)
+            })
+            .collect();
+        *self = Snapshot {
+            name: fresh.name,
+            capabilities: fresh.capabilities,
+            features,
+            polled_in_full: self.polled_in_full || fresh.polled_in_full,
+        };
+    }
+}
+
+/// Returns every monitor with its name, capabilities and the values of the features it supports
+/// in one call. What was read before is returned as it is, so that the panel renders at once;
+/// with `force`, the feature values are read again. The monitors are read at once, taking turns
+/// only with those on the same bus.
+#[tauri::command]
+pub async fn get_state(
+    monitors_state: State<'_, Monitors>,
+    force: Option<bool>,
+) -> JSResult<Vec<MonitorState>> {
+    let force = force.unwrap_or(false);
+    let monitors = monitors_state.monitors.read().await;
+    let mut snapshots = {
+        let cached = monitors_state.snapshots.lock().unwrap();
+        monitors
+            .iter()
+            .map(|worker| cached.get(worker.monitor().identity.as_str()).cloned())
+            .collect::<Vec<_>>()
+    };
+
+    // the name and capabilities are read once, and each again while it is missing: the name at
+    // every call, the capabilities when forced
+    let capabilities: Vec<_> = monitors
+        .iter()
+        .zip(&snapshots)
+        .map(|(worker, snapshot)| {
+            let name_missing = snapshot
+                .as_ref()
+                .is_none_or(|snapshot| snapshot.name.is_none());
+            let caps_missing = snapshot
+                .as_ref()
+                .is_none_or(|snapshot| force && snapshot.capabilities.is_none());
+            (name_missing || caps_missing).then(|| {
+                run(
+                    worker,
+                    CAPABILITIES_TIMEOUT,
+                    Priority::Background,
+                    move |monitor| {
+                        let name = name_missing
+                            .then(|| monitor.get_user_friendly_name().ok().flatten())
+                            .flatten()
+                            .map(|s| s.to_string_lossy().into_owned());
+                        Ok((name, caps_missing.then(|| monitor.get_capabilities())))
+                    },
+                )
+            })
+        })
+        .collect();
+    for (snapshot, read) in snapshots.iter_mut().zip(capabilities) {
+        let Some(read) = read else {
+            continue;
+        };
+        let (name, caps) = match read.await {
+            Ok((name, caps)) => (name, caps.map(|caps| caps.ok())),
+            Err(_) => (None, None),
+        };
+        let snapshot = snapshot.get_or_insert_with(Snapshot::default);
+        if name.is_some() {
+            snapshot.name = name;
+        }
+        if let Some(caps) = caps {
+            snapshot.capabilities = caps.map(Capabilities::new);
+            snapshot.features.clear();
+        }
+    }
+
+    let replies: Vec<Vec<_>> = monitors
+        .iter()
+        .zip(&snapshots)
+        .map(|(worker, snapshot)| {
+            let snapshot = snapshot.as_ref().unwrap();
+            if snapshot.features.is_empty() || force {
+                snapshot
+                    .features()
+                    .into_iter()
+                    .map(|feature| (feature, monitors_state.read_cached(worker, feature, force)))
+                    .collect()
+            } else {
+                Vec::new()
+            }
+        })
+        .collect();
+    let mut read = Vec::new();
+    for (snapshot, replies) in snapshots.iter_mut().zip(replies) {
+        let snapshot = snapshot.as_mut().unwrap();
+        let mut features = Vec::new();
+        for (feature, reply) in replies {
+            snapshot.update(feature, reply.await);
+            features.push(feature);
+        }
+        read.push(features);
+    }
+
+    // the poller may have updated the snapshots meanwhile, so only what was read here is taken
+    let snapshots: Vec<_> = {
+        let mut cached = monitors_state.snapshots.lock().unwrap();
+        cached.retain(|id, _| {
+            monitors
+                .iter()
+                .any(|worker| worker.monitor().identity.as_str() == id)
+        });
+        monitors
+            .iter()
+            .zip(snapshots)
+            .zip(read)
+            .map(|((worker, snapshot), read)| {
+                let snapshot = snapshot.unwrap();
+                match cached.entry(worker.monitor().identity.to_string()) {
+                    Entry::Vacant(entry) => entry.insert(snapshot).clone(),
+                    Entry::Occupied(mut entry) => {
+                        entry.get_mut().merge(snapshot, &read);
+                        entry.get().clone()
+                    }
+                }
+            })
+            .collect()
+    };
+    Ok(monitors
+        .iter()
+        .zip(snapshots)
+        .map(|(worker, snapshot)| MonitorState {
+            info: MonitorInfo::new(worker.monitor()),
+            name: snapshot.name,
+            capabilities: snapshot.capabilities,
+            features: snapshot.features,
+        })
+        .collect())
 }
@@ -35,6 +35,20 @@ pub fn hook_panic() {
     }));
 }
 
+/// Shows a problem that the program works around, such as a user file it leaves out.
+pub fn warn(text: &str) {
+    let wtext: Vec<_> = format!("{text}\0").encode_utf16().collect();
+    let wcaption: Vec<_> = MESSAGE_CAPTION.encode_utf16().collect();
+    unsafe {
+        MessageBoxW(
+            None,
+            PCWSTR::from_raw(wtext.as_ptr()),
+            PCWSTR::from_raw(wcaption.as_ptr()),
+            MB_ICONWARNING,
+        )
+    };
+}
+
 pub fn ensure_singleton() {
     let mut lock_file = env::temp_dir();
     lock_file.push("BrightnessTray.lock");
@@ -3,37 +3,84 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(transparent)]
-pub struct JSError(pub String);
+/// An error as thrown to JS. `code` is the kind of error, e.g. `noReply` or `notSupported`, for
+/// the panel to decide what to show and what to retry; `monitor` and `feature` are what the
+/// command was about, if anything.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JSError {
+    pub code: &'static str,
+    pub message: String,
+    pub monitor: Option<String>,
+    pub feature: Option<String>,
+}
+
+impl JSError {
+    pub fn new(code: &'static str, message: impl Into<String>) -> JSError {
+        JSError {
+            code,
+            message: message.into(),
+            monitor: None,
+            feature: None,
+        }
+    }
+
+    pub fn with_monitor(mut self, id: impl Into<String>) -> JSError {
+        self.monitor = Some(id.into());
+        self
+    }
+
+    pub fn with_feature(mut self, feature: impl ToString) -> JSError {
+        self.feature = Some(feature.to_string());
+        self
+    }
+}
 
 impl fmt::Display for JSError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.message)
     }
 }
 
 impl Error for JSError {}
 
+impl From<monitor::Error> for JSError {
+    fn from(value: monitor::Error) -> Self {
+        JSError::new(value.kind().code(), error_to_message(&value))
+    }
+}
+
+impl From<monitor::ErrorKind> for JSError {
+    fn from(value: monitor::ErrorKind) -> Self {
+        JSError::from(monitor::Error::from(value))
+    }
+}
+
+impl From<std::io::Error> for JSError {
+    fn from(value: std::io::Error) -> Self {
+        JSError::from(monitor::Error::from(value))
+    }
+}
+
 impl From<windows::core::Error> for JSError {
     fn from(value: windows::core::Error) -> Self {
-        JSError(error_to_message(value))
+        JSError::new(monitor::ErrorKind::Io.code(), error_to_message(&value))
     }
 }
 
 impl From<tauri::Error> for JSError {
     fn from(value: tauri::Error) -> Self {
-        JSError(error_to_message(value))
+        JSError::new(monitor::ErrorKind::Io.code(), error_to_message(&value))
     }
 }
 
+/// Messages of invalid arguments, e.g. an unknown feature name.
 impl From<String> for JSError {
     fn from(value: String) -> Self {
-        JSError(value)
+        JSError::new(monitor::ErrorKind::InvalidInput.code(), value)
     }
 }
 
-fn error_to_message<E: Error>(e: E) -> String {
+fn error_to_message<E: Error>(e: &E) -> String {
     #[cfg(debug_assertions)]
     return format!("{e:?}");
     #[cfg(not(debug_assertions))]
@@ -1,6 +1,7 @@
-import { createApp } from "vue";
-import { watchThrottled } from "./watchers";
+import { createApp, watch } from "vue";
+import { invoke } from "@tauri-apps/api";
 import monitorManager from "./monitor";
+import settings from "./settings";
 import BrightnessPanel from "./components/BrightnessPanel.vue";
 import panelState from "./wm";
 import "./style";
@@ -8,12 +9,29 @@ import "./style.global.sass";
 
 createApp(BrightnessPanel).mount("#root");
 
-watchThrottled(
+// the values are kept in sync by the poller on the Rust side
+monitorManager.refresh();
+
+// the poller is paused while the panel is hidden, so what changed meanwhile is read when shown
+watch(
     () => panelState.focused,
-    () => {
-        monitorManager.refresh();
+    (focused) => {
+        if (focused) {
+            monitorManager.refresh(false, true);
+        }
     },
-    { throttle: 10000, immediate: true },
+);
+
+watch(
+    () => settings.pollInterval,
+    (intervalMs) => invoke("set_poll_interval", { intervalMs }),
+    { immediate: true },
+);
+
+watch(
+    () => settings.cacheTtl,
+    (ttlMs) => invoke("set_cache_ttl", { ttlMs }),
+    { immediate: true },
 );
 
 if (import.meta.env.PROD) {
@@ -1,10 +1,34 @@
 import { invoke } from "@tauri-apps/api";
+import { listen } from "@tauri-apps/api/event";
 import { reactive, toRaw, DeepReadonly } from "vue";
 
 export interface Reply {
     current: number;
     maximum: number;
-    source: "ddcci" | "wmi";
+    source: "ddcci" | "ioctl" | "backlight";
+    label: string | null;
+}
+
+export interface Capabilities {
+    kind: string | null;
+    model: string | null;
+    vcp: Record<string, number[]>;
+    mccsVersion: string | null;
+    features: string[];
+    inputs: Input[];
+}
+
+export interface Input {
+    value: number;
+    name: string;
+}
+
+// what commands throw; `code` is e.g. "notSupported", "noReply" or "noSuchMonitor"
+export interface MonitorError {
+    code: string;
+    message: string;
+    monitor: string | null;
+    feature: string | null;
 }
 
 export interface Feature {
@@ -12,87 +36,99 @@ export interface Feature {
     value: Reply;
 }
 
+export interface MonitorInfo {
+    id: string;
+    path: string;
+    powerOffValue: number | null;
+}
+
+// a monitor with everything the panel shows of it, as returned by `get_state`
+export interface MonitorState extends MonitorInfo {
+    name: string | null;
+    capabilities: Capabilities | null;
+    features: Feature[];
+}
+
+// the payload of `monitor-feature-changed`
+export interface FeatureChanged {
+    id: string;
+    feature: string;
+    value: Reply;
+}
+
+export interface Pacing {
+    delayMs: number;
+    floorMs: number;
+    commands: number;
+    failures: number;
+    retries: number;
+    givenUp: number;
+}
+
 export interface Monitor {
     id: string;
+    path: string;
+    // overrides the power-off setting for monitors known to need another value
+    powerOffValue: number | null;
     name: string | null;
     features: Feature[];
+    inputs: Input[];
 }
 
 export class Manager {
     readonly monitors: DeepReadonly<Monitor[]> = reactive([]);
     private refreshing = false;
 
-    private async doRefresh(): Promise<void> {
+    private apply(states: MonitorState[]): void {
         const monitors = this.monitors as Monitor[];
-        await invoke("refresh_monitors");
-        const monitorIds = await invoke<string[]>("get_monitors");
         const monitorMap = new Map(monitors.map((monitor) => [monitor.id, monitor]));
-        Object.assign(
-            monitors,
-            monitorIds.map(
-                (id) =>
-                    monitorMap.get(id) ?? {
-                        id,
-                        name: null,
-                        features: [],
-                    },
-            ),
+        monitors.splice(
+            0,
+            monitors.length,
+            ...states.map(({ id, path, powerOffValue, name, capabilities, features }) => {
+                const monitor = monitorMap.get(id) ?? {
+                    id,
+                    path,
+                    powerOffValue,
+                    name,
+                    features: [],
+                    inputs: [],
+                };
+                // the monitor may have moved to another port
+                monitor.path = path;
+                monitor.powerOffValue = powerOffValue;
+                monitor.name = name;
+                monitor.inputs = capabilities?.inputs ?? [];
+                monitor.features = features.map(({ name, value }) => {
+                    const item = monitor.features.find((feature) => feature.name == name);
+                    if (item) {
+                        Object.assign(item.value, value);
+                        return item;
+                    }
+                    return { name, value };
+                });
+                return monitor;
+            }),
         );
-        const pool: Promise<void>[] = [];
-        for (const monitor of monitors) {
-            if (monitor.name == null) {
-                pool.push(
-                    (async () => {
-                        try {
-                            monitor.name = await invoke<string | null>(
-                                "get_monitor_user_friendly_name",
-                                { id: monitor.id },
-                            );
-                        } catch {}
-                    })(),
-                );
-            }
+    }
+
+    private async doRefresh(rescan: boolean, reread: boolean): Promise<void> {
+        if (rescan) {
+            await invoke("refresh_monitors");
         }
-        for (const monitor of monitors) {
-            pool.push(
-                (async () => {
-                    const featureNames = monitor.features.length
-                        ? monitor.features.map((feature) => feature.name)
-                        : ["luminance", "contrast", "brightness", "volume", "powerstate"];
-                    for (const name of featureNames) {
-                        let value: Reply | undefined;
-                        try {
-                            value = await invoke<Reply>("get_monitor_feature", {
-                                id: monitor.id,
-                                feature: name,
-                            });
-                        } catch {}
-                        const idx = monitor.features.findIndex((feature) => feature.name == name);
-                        if (value) {
-                            const item = monitor.features[idx];
-                            if (item) {
-                                Object.assign(item.value, value);
-                            } else {
-                                monitor.features.push({
-                                    name,
-                                    value,
-                                });
-                            }
-                        } else if (idx != -1) {
-                            monitor.features.splice(idx, 1);
-                        }
-                    }
-                })(),
-            );
+        // what was read before renders at once, then the values are read again
+        this.apply(await invoke<MonitorState[]>("get_state"));
+        if (reread) {
+            this.apply(await invoke<MonitorState[]>("get_state", { force: true }));
         }
-        await Promise.allSettled(pool);
     }
 
-    async refresh(): Promise<void> {
+    // rescanning is unnecessary after a `monitor-added` or `monitor-removed` event
+    async refresh(rescan = true, reread = rescan): Promise<void> {
         if (!this.refreshing) {
             this.refreshing = true;
             try {
-                await this.doRefresh();
+                await this.doRefresh(rescan, reread);
             } finally {
                 this.refreshing = false;
             }
@@ -116,21 +152,75 @@ export class Manager {
         return monitor.features[idx];
     }
 
-    async setFeature(id: string, name: string, value: number): Promise<void> {
+    async setFeature(id: string, name: string, value: number, verify = false): Promise<void> {
         const feature = this.getFeature(id, name) as Feature;
         if (feature.value.current != value) {
-            Object.assign(
-                feature.value,
-                await invoke<Reply>("set_monitor_feature", {
-                    id,
-                    feature: name,
-                    value,
-                }),
-            );
+            try {
+                Object.assign(
+                    feature.value,
+                    await invoke<Reply>("set_monitor_feature", {
+                        id,
+                        feature: name,
+                        value,
+                        verify,
+                    }),
+                );
+            } catch (e) {
+                const error = e as MonitorError;
+                console.error(`${error.code}: ${error.message}`);
+                // show what the monitor has rather than the value that was not applied
+                await this.reread(id, name);
+            }
+        }
+    }
+
+    private async reread(id: string, name: string): Promise<void> {
+        const monitor = this.getMonitor(id) as Monitor;
+        try {
+            const value = await invoke<Reply>("get_monitor_feature", {
+                id,
+                feature: name,
+                force: true,
+            });
+            Object.assign((this.getFeature(id, name) as Feature).value, value);
+        } catch (e) {
+            if ((e as MonitorError).code == "notSupported") {
+                monitor.features = monitor.features.filter((feature) => feature.name != name);
+            }
+        }
+    }
+
+    async setInput(id: string, value: number): Promise<void> {
+        const feature = this.getFeature(id, "input") as Feature;
+        if (feature.value.current != value) {
+            const reply = await invoke<Reply | null>("set_monitor_input", {
+                id,
+                input: String(value),
+            });
+            if (reply) {
+                Object.assign(feature.value, reply);
+            }
         }
     }
+
+    // takes a value the poller found changed, e.g. from the OSD of the monitor
+    update({ id, feature: name, value }: FeatureChanged): void {
+        const monitor = this.monitors.find((monitor) => monitor.id == id) as Monitor | undefined;
+        const feature = monitor?.features.find((feature) => feature.name == name);
+        if (feature) {
+            Object.assign(feature.value, value);
+        }
+    }
+
+    async getPacing(id: string): Promise<Pacing> {
+        return await invoke<Pacing>("get_monitor_pacing", { id });
+    }
 }
 
 const manager = new Manager();
 
+listen("monitor-added", () => manager.refresh(false));
+listen("monitor-removed", () => manager.refresh(false));
+listen<FeatureChanged>("monitor-feature-changed", ({ payload }) => manager.update(payload));
+
 export default manager;
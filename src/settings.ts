@@ -2,12 +2,18 @@ import { reactive } from "vue";
 
 export interface Settings {
     updateInterval: number;
+    // how often the monitors are polled for values changed elsewhere, in milliseconds
+    pollInterval: number;
+    // how long a value read from a monitor is reused by other readers, in milliseconds
+    cacheTtl: number;
     ddcPowerOffValue: number;
     writingMode: string;
 }
 
 export default reactive<Settings>({
     updateInterval: 200,
+    pollInterval: 5000,
+    cacheTtl: 2000,
     ddcPowerOffValue: 6,
     writingMode: "horizontal-tb",
 });